@@ -1,6 +1,18 @@
 pub use sea_orm_migration::prelude::*;
 
 mod m20240714_065956_create_clipboard_table;
+mod m20240801_000001_create_clipboard_fts;
+mod m20240805_000001_add_size_column;
+mod m20240810_000001_add_pinned_columns;
+mod m20240815_000001_create_tag_tables;
+mod m20240820_000001_add_source_columns;
+mod m20240825_000001_add_rich_text_columns;
+mod m20240830_000001_create_representation_table;
+mod m20240905_000001_add_subtype_column;
+mod m20240910_000001_create_file_snapshot_table;
+mod m20240915_000001_add_version_key_column;
+mod m20240920_000001_add_summary_column;
+mod m20240925_000001_encode_file_paths;
 
 pub struct Migrator;
 
@@ -9,6 +21,18 @@ impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
         vec![
             Box::new(m20240714_065956_create_clipboard_table::Migration),
+            Box::new(m20240801_000001_create_clipboard_fts::Migration),
+            Box::new(m20240805_000001_add_size_column::Migration),
+            Box::new(m20240810_000001_add_pinned_columns::Migration),
+            Box::new(m20240815_000001_create_tag_tables::Migration),
+            Box::new(m20240820_000001_add_source_columns::Migration),
+            Box::new(m20240825_000001_add_rich_text_columns::Migration),
+            Box::new(m20240830_000001_create_representation_table::Migration),
+            Box::new(m20240905_000001_add_subtype_column::Migration),
+            Box::new(m20240910_000001_create_file_snapshot_table::Migration),
+            Box::new(m20240915_000001_add_version_key_column::Migration),
+            Box::new(m20240920_000001_add_summary_column::Migration),
+            Box::new(m20240925_000001_encode_file_paths::Migration),
         ]
     }
 }
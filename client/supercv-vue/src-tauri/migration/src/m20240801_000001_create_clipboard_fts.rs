@@ -0,0 +1,49 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+// host_clipboard.content 的 FTS5 索引, 由触发器保持同步
+const UP_SQL: &str = r#"
+CREATE VIRTUAL TABLE IF NOT EXISTS host_clipboard_fts USING fts5(
+    content,
+    content = 'host_clipboard',
+    content_rowid = 'id',
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+CREATE TRIGGER IF NOT EXISTS host_clipboard_fts_ai AFTER INSERT ON host_clipboard BEGIN
+    INSERT INTO host_clipboard_fts(rowid, content) VALUES (new.id, new.content);
+END;
+
+CREATE TRIGGER IF NOT EXISTS host_clipboard_fts_ad AFTER DELETE ON host_clipboard BEGIN
+    INSERT INTO host_clipboard_fts(host_clipboard_fts, rowid, content) VALUES ('delete', old.id, old.content);
+END;
+
+CREATE TRIGGER IF NOT EXISTS host_clipboard_fts_au AFTER UPDATE OF content ON host_clipboard BEGIN
+    INSERT INTO host_clipboard_fts(host_clipboard_fts, rowid, content) VALUES ('delete', old.id, old.content);
+    INSERT INTO host_clipboard_fts(rowid, content) VALUES (new.id, new.content);
+END;
+
+INSERT INTO host_clipboard_fts(host_clipboard_fts) VALUES ('rebuild');
+"#;
+
+const DOWN_SQL: &str = r#"
+DROP TRIGGER IF EXISTS host_clipboard_fts_ai;
+DROP TRIGGER IF EXISTS host_clipboard_fts_ad;
+DROP TRIGGER IF EXISTS host_clipboard_fts_au;
+DROP TABLE IF EXISTS host_clipboard_fts;
+"#;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.get_connection().execute_unprepared(UP_SQL).await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.get_connection().execute_unprepared(DOWN_SQL).await?;
+        Ok(())
+    }
+}
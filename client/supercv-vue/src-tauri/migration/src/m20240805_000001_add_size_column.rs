@@ -0,0 +1,62 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(HostClipboard::Table)
+                    .add_column(
+                        ColumnDef::new(HostClipboard::Size)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 文本记录的大小可以直接回填, 图片和文件保持 0
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE host_clipboard SET size = length(CAST(content AS BLOB)) WHERE type = 0",
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(HostClipboard::Table)
+                    .drop_column(HostClipboard::Size)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+enum HostClipboard {
+    Table,
+    Size,
+}
+
+impl Iden for HostClipboard {
+    fn unquoted(&self, s: &mut dyn Write) {
+        write!(
+            s,
+            "{}",
+            match self {
+                Self::Table => "host_clipboard",
+                Self::Size => "size",
+            }
+        )
+        .unwrap();
+    }
+}
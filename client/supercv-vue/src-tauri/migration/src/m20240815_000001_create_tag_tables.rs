@@ -0,0 +1,143 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Tag::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Tag::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Tag::Name).string().not_null().unique_key())
+                    .col(ColumnDef::new(Tag::Timestamp).integer().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(ClipboardTag::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ClipboardTag::ClipboardId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ClipboardTag::TagId).integer().not_null())
+                    .primary_key(
+                        Index::create()
+                            .col(ClipboardTag::ClipboardId)
+                            .col(ClipboardTag::TagId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_clipboard_tag_clipboard_id")
+                            .from(ClipboardTag::Table, ClipboardTag::ClipboardId)
+                            .to(HostClipboard::Table, HostClipboard::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_clipboard_tag_tag_id")
+                            .from(ClipboardTag::Table, ClipboardTag::TagId)
+                            .to(Tag::Table, Tag::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_clipboard_tag_tag_id")
+                    .table(ClipboardTag::Table)
+                    .col(ClipboardTag::TagId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ClipboardTag::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Tag::Table).to_owned())
+            .await
+    }
+}
+
+enum HostClipboard {
+    Table,
+    Id,
+}
+
+enum Tag {
+    Table,
+    Id,
+    Name,
+    Timestamp,
+}
+
+enum ClipboardTag {
+    Table,
+    ClipboardId,
+    TagId,
+}
+
+impl Iden for HostClipboard {
+    fn unquoted(&self, s: &mut dyn Write) {
+        write!(
+            s,
+            "{}",
+            match self {
+                Self::Table => "host_clipboard",
+                Self::Id => "id",
+            }
+        )
+        .unwrap();
+    }
+}
+
+impl Iden for Tag {
+    fn unquoted(&self, s: &mut dyn Write) {
+        write!(
+            s,
+            "{}",
+            match self {
+                Self::Table => "tag",
+                Self::Id => "id",
+                Self::Name => "name",
+                Self::Timestamp => "timestamp",
+            }
+        )
+        .unwrap();
+    }
+}
+
+impl Iden for ClipboardTag {
+    fn unquoted(&self, s: &mut dyn Write) {
+        write!(
+            s,
+            "{}",
+            match self {
+                Self::Table => "clipboard_tag",
+                Self::ClipboardId => "clipboard_id",
+                Self::TagId => "tag_id",
+            }
+        )
+        .unwrap();
+    }
+}
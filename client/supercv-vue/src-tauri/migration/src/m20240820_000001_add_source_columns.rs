@@ -0,0 +1,67 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 来源应用(WM_CLASS)和窗口标题, 无法获取时为 NULL; SQLite 一次只能添加一列
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(HostClipboard::Table)
+                    .add_column(ColumnDef::new(HostClipboard::SourceApp).string().null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(HostClipboard::Table)
+                    .add_column(ColumnDef::new(HostClipboard::SourceTitle).string().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(HostClipboard::Table)
+                    .drop_column(HostClipboard::SourceTitle)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(HostClipboard::Table)
+                    .drop_column(HostClipboard::SourceApp)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+enum HostClipboard {
+    Table,
+    SourceApp,
+    SourceTitle,
+}
+
+impl Iden for HostClipboard {
+    fn unquoted(&self, s: &mut dyn Write) {
+        write!(
+            s,
+            "{}",
+            match self {
+                Self::Table => "host_clipboard",
+                Self::SourceApp => "source_app",
+                Self::SourceTitle => "source_title",
+            }
+        )
+        .unwrap();
+    }
+}
@@ -0,0 +1,183 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, Statement};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 一次复制提供的每种格式一行, 小内容直接保存在 data, 大内容保存在 blob store 中由 path 引用
+        manager
+            .create_table(
+                Table::create()
+                    .table(Representation::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Representation::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Representation::ClipboardId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Representation::Format).string().not_null())
+                    .col(ColumnDef::new(Representation::Data).binary().null())
+                    .col(ColumnDef::new(Representation::Path).string().null())
+                    .col(
+                        ColumnDef::new(Representation::Size)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_representation_clipboard_id")
+                            .from(Representation::Table, Representation::ClipboardId)
+                            .to(HostClipboard::Table, HostClipboard::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_representation_clipboard_id_format")
+                    .table(Representation::Table)
+                    .col(Representation::ClipboardId)
+                    .col(Representation::Format)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        migrate_existing_rows(manager).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Representation::Table).to_owned())
+            .await
+    }
+}
+
+// 已有记录按类型生成对应的格式
+async fn migrate_existing_rows(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    let db = manager.get_connection();
+
+    // 文本和富文本
+    db.execute_unprepared(
+        "INSERT INTO representation (clipboard_id, format, data, size)
+         SELECT id, 'text/plain', CAST(content AS BLOB), LENGTH(CAST(content AS BLOB))
+         FROM host_clipboard WHERE type IN (0, 3);
+         INSERT INTO representation (clipboard_id, format, data, size)
+         SELECT id, 'text/html', CAST(html AS BLOB), LENGTH(CAST(html AS BLOB))
+         FROM host_clipboard WHERE type = 3 AND html IS NOT NULL;
+         INSERT INTO representation (clipboard_id, format, data, size)
+         SELECT id, 'text/rtf', CAST(rtf AS BLOB), LENGTH(CAST(rtf AS BLOB))
+         FROM host_clipboard WHERE type = 3 AND rtf IS NOT NULL;",
+    )
+    .await?;
+
+    // 图片和文件之前都以文件列表的方式粘贴, 转换为 text/uri-list
+    let rows = db
+        .query_all(Statement::from_string(
+            manager.get_database_backend(),
+            "SELECT id, path FROM host_clipboard WHERE type IN (1, 2)",
+        ))
+        .await?;
+    for row in rows {
+        let id: i32 = row.try_get("", "id")?;
+        let path: String = row.try_get("", "path")?;
+        let uri_list = path
+            .split('\n')
+            .filter(|p| !p.is_empty())
+            .map(file_uri)
+            .collect::<Vec<_>>()
+            .join("\r\n");
+        db.execute(Statement::from_sql_and_values(
+            manager.get_database_backend(),
+            "INSERT INTO representation (clipboard_id, format, data, size) VALUES (?, ?, ?, ?)",
+            [
+                id.into(),
+                "text/uri-list".into(),
+                uri_list.as_bytes().to_vec().into(),
+                (uri_list.len() as i64).into(),
+            ],
+        ))
+        .await?;
+    }
+    Ok(())
+}
+
+// 本地路径转换为 file:// URI
+fn file_uri(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let mut uri = String::from("file://");
+    if !path.starts_with('/') {
+        uri.push('/');
+    }
+    for b in path.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                uri.push(b as char)
+            }
+            _ => uri.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    uri
+}
+
+enum HostClipboard {
+    Table,
+    Id,
+}
+
+enum Representation {
+    Table,
+    Id,
+    ClipboardId,
+    Format,
+    Data,
+    Path,
+    Size,
+}
+
+impl Iden for HostClipboard {
+    fn unquoted(&self, s: &mut dyn Write) {
+        write!(
+            s,
+            "{}",
+            match self {
+                Self::Table => "host_clipboard",
+                Self::Id => "id",
+            }
+        )
+        .unwrap();
+    }
+}
+
+impl Iden for Representation {
+    fn unquoted(&self, s: &mut dyn Write) {
+        write!(
+            s,
+            "{}",
+            match self {
+                Self::Table => "representation",
+                Self::Id => "id",
+                Self::ClipboardId => "clipboard_id",
+                Self::Format => "format",
+                Self::Data => "data",
+                Self::Path => "path",
+                Self::Size => "size",
+            }
+        )
+        .unwrap();
+    }
+}
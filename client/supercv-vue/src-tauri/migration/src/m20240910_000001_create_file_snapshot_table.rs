@@ -0,0 +1,139 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 复制文件时保存在 blob store 中的副本, 原文件被移动或删除后用于粘贴
+        manager
+            .create_table(
+                Table::create()
+                    .table(FileSnapshot::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(FileSnapshot::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(FileSnapshot::ClipboardId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(FileSnapshot::OriginalPath)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(FileSnapshot::BlobPath).string().not_null())
+                    .col(
+                        ColumnDef::new(FileSnapshot::Size)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_file_snapshot_clipboard_id")
+                            .from(FileSnapshot::Table, FileSnapshot::ClipboardId)
+                            .to(HostClipboard::Table, HostClipboard::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_file_snapshot_clipboard_id_original_path")
+                    .table(FileSnapshot::Table)
+                    .col(FileSnapshot::ClipboardId)
+                    .col(FileSnapshot::OriginalPath)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // 原文件已不存在且没有副本的文件条目
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(HostClipboard::Table)
+                    .add_column(
+                        ColumnDef::new(HostClipboard::Broken)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(HostClipboard::Table)
+                    .drop_column(HostClipboard::Broken)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(FileSnapshot::Table).to_owned())
+            .await
+    }
+}
+
+enum HostClipboard {
+    Table,
+    Id,
+    Broken,
+}
+
+enum FileSnapshot {
+    Table,
+    Id,
+    ClipboardId,
+    OriginalPath,
+    BlobPath,
+    Size,
+}
+
+impl Iden for HostClipboard {
+    fn unquoted(&self, s: &mut dyn Write) {
+        write!(
+            s,
+            "{}",
+            match self {
+                Self::Table => "host_clipboard",
+                Self::Id => "id",
+                Self::Broken => "broken",
+            }
+        )
+        .unwrap();
+    }
+}
+
+impl Iden for FileSnapshot {
+    fn unquoted(&self, s: &mut dyn Write) {
+        write!(
+            s,
+            "{}",
+            match self {
+                Self::Table => "file_snapshot",
+                Self::Id => "id",
+                Self::ClipboardId => "clipboard_id",
+                Self::OriginalPath => "original_path",
+                Self::BlobPath => "blob_path",
+                Self::Size => "size",
+            }
+        )
+        .unwrap();
+    }
+}
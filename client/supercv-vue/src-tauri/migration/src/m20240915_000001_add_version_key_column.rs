@@ -0,0 +1,80 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 同一路径的文件的不同版本共用 version_key, 其他条目为 NULL
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(HostClipboard::Table)
+                    .add_column(ColumnDef::new(HostClipboard::VersionKey).string().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_host_clipboard_version_key")
+                    .table(HostClipboard::Table)
+                    .col(HostClipboard::VersionKey)
+                    .to_owned(),
+            )
+            .await?;
+
+        // 旧的文件条目以路径计算 hash, 正好可以作为 version_key;
+        // 截图的 content 为 "Img: 宽x高 (大小)", 不属于任何文件
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE host_clipboard SET version_key = hash \
+                 WHERE type = 2 OR (type = 1 AND content NOT GLOB 'Img: [0-9]*x[0-9]* (*')",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_host_clipboard_version_key")
+                    .table(HostClipboard::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(HostClipboard::Table)
+                    .drop_column(HostClipboard::VersionKey)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+enum HostClipboard {
+    Table,
+    VersionKey,
+}
+
+impl Iden for HostClipboard {
+    fn unquoted(&self, s: &mut dyn Write) {
+        write!(
+            s,
+            "{}",
+            match self {
+                Self::Table => "host_clipboard",
+                Self::VersionKey => "version_key",
+            }
+        )
+        .unwrap();
+    }
+}
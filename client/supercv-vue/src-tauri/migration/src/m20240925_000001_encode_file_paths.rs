@@ -0,0 +1,49 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, Statement};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 多文件条目的 path 之前以换行分隔, 文件名中的换行会被拆开; 改为 JSON 数组
+        let db = manager.get_connection();
+        let rows = db
+            .query_all(Statement::from_string(
+                manager.get_database_backend(),
+                "SELECT id, path FROM host_clipboard WHERE type = 2 AND instr(path, char(10)) > 0",
+            ))
+            .await?;
+        for row in rows {
+            let id: i32 = row.try_get("", "id")?;
+            let path: String = row.try_get("", "path")?;
+            let paths: Vec<&str> = path.split('\n').filter(|p| !p.is_empty()).collect();
+            let placeholders = vec!["?"; paths.len()].join(", ");
+            let mut values: Vec<Value> = paths.into_iter().map(Into::into).collect();
+            values.push(id.into());
+            db.execute(Statement::from_sql_and_values(
+                manager.get_database_backend(),
+                format!(
+                    "UPDATE host_clipboard SET path = json_array({}) WHERE id = ?",
+                    placeholders
+                ),
+                values,
+            ))
+            .await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE host_clipboard \
+                 SET path = (SELECT group_concat(value, char(10)) FROM json_each(host_clipboard.path)) \
+                 WHERE type = 2 AND path LIKE '[%'",
+            )
+            .await?;
+        Ok(())
+    }
+}
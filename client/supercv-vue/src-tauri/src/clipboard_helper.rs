@@ -1,36 +1,73 @@
 use clipboard_rs::{Clipboard, ClipboardContent, ClipboardContext, ClipboardWatcher, ClipboardWatcherContext, RustImageData, WatcherShutdown};
-use log::{debug, error};
-use sea_orm::DatabaseConnection;
+use log::{debug, error, info};
+use sea_orm::{DatabaseConnection, DbErr};
+use serde::Serialize;
 use std::io;
+use std::path::Path;
 use std::sync::Arc;
 use clipboard_rs::common::RustImage;
 use tokio::sync::Mutex;
 
+use crate::core::blob_store::{BLOB_STORE, IMG_BLOB_EXT};
+use crate::core::capture::{CaptureControl, CaptureState};
 use crate::core::clipboard::ClipboardHandle;
+use crate::core::concealed::{self, ConcealedClip, CONCEALED_STASH};
+use crate::core::join::{self, JoinOptions};
+use crate::core::large_text;
+use crate::core::own_write;
+use crate::core::paste_queue::{QueueMode, QueueState, SharedPasteQueue};
+use crate::core::paste_watch;
+use crate::core::snapshot;
+use crate::core::thumbnail;
+use crate::core::transform::{self, Transform};
+use crate::protocol::{Resource, Route};
+use crate::core::retention::{self, PurgeReport};
+use crate::core::pasteboard::{ContentType, FORMAT_HTML, FORMAT_PNG, FORMAT_RTF, FORMAT_TEXT, FORMAT_URI_LIST, REPRESENTATION_BLOB_EXT};
+use crate::core::query::{self, QueryError};
 use crate::db::connection::init_db_connection;
 use crate::db::crud;
+use crate::db::crud::host_clipboard::SearchHit;
 use crate::db::entities::host_clipboard::Model;
+use crate::db::entities::representation::Model as RepresentationModel;
+use crate::db::entities::tag::Model as TagModel;
 use crate::time_it;
 use crate::utils::config::{UserConfig, CONFIG};
+use crate::utils::time::get_current_timestamp;
 use crate::utils::{config, logger};
 
 pub struct ClipboardHelper {
     db: Arc<Mutex<DatabaseConnection>>,
     ctx: ClipboardContext,
+    capture: Arc<CaptureControl>,
+    paste_queue: Arc<SharedPasteQueue>,
     watcher_shutdown: WatcherShutdown,
 }
 
 impl ClipboardHelper {
     pub async fn new(log_level: Option<i32>, sql_level: Option<i32>) -> Self {
         logger::init_logger(log_level, sql_level);
+        for e in CONFIG.read().unwrap().ignore_errors.iter() {
+            error!("Invalid ignore rule: {}", e);
+        }
         // 初始化数据库连接
         let db_connection = init_db_connection(None)
             .await
             .expect("Failed to connect to database");
         let db = Arc::new(Mutex::new(db_connection));
 
-        // 创建 ClipboardHandle
-        let clipboard_manager = ClipboardHandle::new(db.clone());
+        // 定时清理过期记录和文件
+        retention::spawn_retention_worker(db.clone());
+        // 隐藏内容到期后从内存中清除
+        CONCEALED_STASH.spawn_expiry_worker();
+
+        // 读取上次退出时的记录状态
+        let capture_path = CONFIG.read().unwrap().config_dir.join("capture.toml");
+        let capture = Arc::new(CaptureControl::load(capture_path));
+
+        // 创建 ClipboardHandle, 监听到其他程序写入时中断粘贴队列
+        let paste_queue = Arc::new(SharedPasteQueue::default());
+        let clipboard_manager =
+            ClipboardHandle::new(db.clone(), capture.clone(), paste_queue.clone());
 
         let mut watcher = ClipboardWatcherContext::new().unwrap();
         let watcher_shutdown = watcher
@@ -44,6 +81,8 @@ impl ClipboardHelper {
         Self {
             db,
             ctx: ClipboardContext::new().unwrap(),
+            capture,
+            paste_queue,
             watcher_shutdown,
             // watcher_handle,
         }
@@ -54,36 +93,175 @@ impl ClipboardHelper {
         &self,
         num: u64,
         type_list: Option<Vec<i32>>,
+        tag_list: Option<Vec<i32>>,
+        subtype_list: Option<Vec<i32>>,
+        group_versions: bool,
     ) -> Result<Vec<Model>, Box<dyn std::error::Error>> {
         let db_guard = self.db.lock().await;
         let all_entries = time_it!(async {
-            crud::host_clipboard::get_clipboards_by_type_list(&db_guard, None, Some(num), type_list)
+            crud::host_clipboard::get_clipboards_by_type_list(
+                &db_guard,
+                Some(num),
+                type_list,
+                tag_list,
+                subtype_list,
+                group_versions,
+            )
         })
         .await?;
+        drop(db_guard);
+        let mut all_entries = all_entries;
+        self.refresh_broken_flags(all_entries.iter_mut()).await?;
         Ok(all_entries)
     }
 
+    // 文件检查不持有数据库锁, 慢速或网络磁盘上的路径不会阻塞其他操作
+    async fn refresh_broken_flags<'a, I>(&self, entries: I) -> Result<(), DbErr>
+    where
+        I: IntoIterator<Item = &'a mut Model>,
+    {
+        let mut entries: Vec<&mut Model> = entries.into_iter().collect();
+        let snapshotted = {
+            let db_guard = self.db.lock().await;
+            crud::host_clipboard::get_snapshotted_paths(&db_guard, entries.iter().map(|e| &**e))
+                .await?
+        };
+        let changes = crud::host_clipboard::check_broken_flags(
+            entries.iter_mut().map(|e| &mut **e),
+            &snapshotted,
+        );
+        if changes.is_empty() {
+            return Ok(());
+        }
+        let db_guard = self.db.lock().await;
+        crud::host_clipboard::save_broken_flags(&db_guard, changes).await
+    }
+
     async fn search_clipboards(
         &self,
         query: &str,
         num: u64,
         type_list: Option<Vec<i32>>,
-    ) -> Result<Vec<Model>, Box<dyn std::error::Error>> {
+        tag_list: Option<Vec<i32>>,
+    ) -> Result<Vec<SearchHit>, SearchError> {
+        let search = query::parse(query).map_err(SearchError::Parse)?;
         let db_guard = self.db.lock().await;
         let all_entries = time_it!(async {
-            crud::host_clipboard::get_clipboards_by_type_list(
-                &db_guard,
-                Some(query),
-                Some(num),
-                type_list,
-            )
+            crud::host_clipboard::search_clipboards(&db_guard, &search, Some(num), type_list, tag_list)
         })
-        .await?;
+        .await
+        .map_err(|e| SearchError::Database {
+            message: e.to_string(),
+        })?;
+        drop(db_guard);
+        let mut all_entries = all_entries;
+        self.refresh_broken_flags(all_entries.iter_mut().map(|hit| &mut hit.entry))
+            .await
+            .map_err(|e| SearchError::Database {
+                message: e.to_string(),
+            })?;
         Ok(all_entries)
     }
 
 
     pub async fn set(&self, items: Vec<Model>) -> Result<(), String> {
+        // 剪贴板中只能保留一段文本, 多条文本默认按行连接
+        if items.len() > 1 {
+            return self.set_joined(items, JoinOptions::default()).await;
+        }
+        self.set_with(items, true).await
+    }
+
+    // 多条文本按 items 的顺序连接后粘贴; 文件和图片混选时以文件列表粘贴
+    pub async fn set_joined(&self, items: Vec<Model>, options: JoinOptions) -> Result<(), String> {
+        if items.is_empty() {
+            return Err("No entries to paste".into());
+        }
+        let ids: Vec<i32> = items.iter().map(|item| item.id).collect();
+        let text_types = [ContentType::Text.to_i32(), ContentType::RichText.to_i32()];
+        let file_types = [
+            ContentType::Image.to_i32(),
+            ContentType::File.to_i32(),
+            ContentType::Directory.to_i32(),
+        ];
+        let content = if items.iter().all(|item| text_types.contains(&item.r#type)) {
+            let texts = items
+                .into_iter()
+                .map(full_text)
+                .collect::<io::Result<Vec<_>>>()
+                .map_err(|e| {
+                    error!("Error loading large text: {}", e);
+                    e.to_string()
+                })?;
+            ClipboardContent::Text(join::join(&texts, &options))
+        } else if items.iter().all(|item| file_types.contains(&item.r#type)) {
+            let (paths, _) = self.resolve_file_paths(&items).await?;
+            ClipboardContent::Files(paths)
+        } else {
+            return Err("Text and file entries can not be pasted together".into());
+        };
+
+        self.ctx
+            .set(vec![content, own_write::marker(&ids)])
+            .map_err(|e| {
+                error!("Error setting clipboard: {}", e);
+                e.to_string()
+            })?;
+        self.after_paste(ids).await;
+        Ok(())
+    }
+
+    // 转换后的文本与复制时的格式不再对应, 只以纯文本粘贴
+    pub async fn set_transformed(
+        &self,
+        item: Model,
+        transforms: Vec<Transform>,
+    ) -> Result<(), String> {
+        let text_types = [ContentType::Text.to_i32(), ContentType::RichText.to_i32()];
+        if !text_types.contains(&item.r#type) {
+            return Err("Only text entries can be transformed".into());
+        }
+        let text = full_text(item.clone()).map_err(|e| {
+            error!("Error loading large text: {}", e);
+            e.to_string()
+        })?;
+        let content = transform::apply(&text, &transforms).map_err(|e| e.to_string())?;
+        let item = Model {
+            r#type: ContentType::Text.to_i32(),
+            path: String::new(),
+            content,
+            html: None,
+            rtf: None,
+            ..item
+        };
+        self.set_with(vec![item], false).await
+    }
+
+    // replay 为 false 时不按复制时的格式还原
+    async fn set_with(&self, items: Vec<Model>, replay: bool) -> Result<(), String> {
+        let ids: Vec<i32> = items.iter().map(|item| item.id).collect();
+        self.write(items, replay).await?;
+        self.after_paste(ids).await;
+        Ok(())
+    }
+
+    // 监听线程会忽略这次写入, 是否移到最前只由配置决定
+    async fn after_paste(&self, ids: Vec<i32>) {
+        let move_to_top = CONFIG.read().unwrap().user_config.paste_config.move_to_top;
+        if move_to_top {
+            let db_guard = self.db.lock().await;
+            let timestamp = get_current_timestamp();
+            if let Err(e) =
+                crud::host_clipboard::touch_clipboard_entries(&db_guard, ids, timestamp).await
+            {
+                error!("Error moving pasted entries to top: {}", e);
+            }
+        }
+    }
+
+    // 写入系统剪贴板, 带上 own_write 标记
+    async fn write(&self, items: Vec<Model>, replay: bool) -> Result<(), String> {
+        let ids: Vec<i32> = items.iter().map(|item| item.id).collect();
         let first_type = items.first().map(|item| item.r#type);
 
         // Ensure all items have the same type
@@ -91,12 +269,44 @@ impl ClipboardHelper {
             return Err("All items must have the same type".into());
         }
 
+        // 文件条目的原文件不存在时使用副本
+        let (file_paths, restored) = match first_type {
+            Some(1) | Some(2) | Some(4) => self.resolve_file_paths(&items).await?,
+            _ => (Vec::new(), false),
+        };
+
+        // 单条记录优先按复制时的全部格式还原, 使用了副本时格式中的路径已失效;
+        // 图片条目没有保存 PNG 时原格式只有文件路径, 改为以位图粘贴
+        if let ([item], true, false) = (items.as_slice(), replay, restored) {
+            let required = (item.r#type == ContentType::Image.to_i32()).then_some(FORMAT_PNG);
+            match self.set_representations(item.id, required).await {
+                Ok(true) => return Ok(()),
+                Ok(false) => {}
+                Err(e) => error!("Error setting representations of {}: {}", item.id, e),
+            }
+        }
+
         // Determine clipboard content based on the type
-        let clipboard_content: Vec<ClipboardContent> = match first_type {
-            Some(0) => items.into_iter().map(|item| ClipboardContent::Text(item.content)).collect(),
-            Some(1) | Some(2) => {
-                let paths: Vec<_> = items.into_iter().map(|item| item.path).collect();
-                return self.ctx.set_files(paths)
+        let mut clipboard_content: Vec<ClipboardContent> = match first_type {
+            Some(0) => items
+                .into_iter()
+                .map(|item| full_text(item).map(ClipboardContent::Text))
+                .collect::<io::Result<_>>()
+                .map_err(|e| {
+                    error!("Error loading large text: {}", e);
+                    e.to_string()
+                })?,
+            // 同时写入所有格式, 由目标应用选择
+            Some(3) => items.into_iter().flat_map(rich_text_contents).collect(),
+            // 剪贴板只能放一张位图, 多张图片仍以文件列表粘贴
+            Some(1) if file_paths.len() == 1 => image_contents(file_paths).map_err(|e| {
+                error!("Error loading image: {}", e);
+                e.to_string()
+            })?,
+            Some(1) | Some(2) | Some(4) => {
+                return self
+                    .ctx
+                    .set(vec![ClipboardContent::Files(file_paths), own_write::marker(&ids)])
                     .map_err(|e| {
                         error!("Error setting files: {}", e);
                         e.to_string()
@@ -106,12 +316,97 @@ impl ClipboardHelper {
         };
 
         // Set clipboard content
+        clipboard_content.push(own_write::marker(&ids));
         self.ctx.set(clipboard_content)
             .map_err(|e| {
                 error!("Error setting clipboard: {}", e);
                 e.to_string()
             })
     }
+
+    // 返回要粘贴的路径以及是否使用了副本; 原文件和副本都没有时标记为 broken 并返回错误
+    async fn resolve_file_paths(&self, items: &[Model]) -> Result<(Vec<String>, bool), String> {
+        let db_guard = self.db.lock().await;
+        let ids = items.iter().map(|item| item.id).collect();
+        let snapshots = crud::file_snapshot::get_snapshots(&db_guard, ids)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut paths = Vec::new();
+        let mut restored = false;
+        for item in items {
+            for path in item_paths(item) {
+                if Path::new(&path).exists() {
+                    paths.push(path);
+                    continue;
+                }
+                let snapshot = snapshots
+                    .iter()
+                    .find(|s| s.clipboard_id == item.id && s.original_path == path);
+                match snapshot.map(|s| snapshot::restore(&s.blob_path, &s.original_path)) {
+                    Some(Ok(restored_path)) => {
+                        restored = true;
+                        paths.push(restored_path.to_string_lossy().into_owned());
+                    }
+                    Some(Err(e)) => {
+                        error!("Error restoring snapshot of {}: {}", path, e);
+                        return Err(format!("Failed to restore {}: {}", path, e));
+                    }
+                    None => {
+                        let mut item = item.clone();
+                        if let Err(e) =
+                            crud::host_clipboard::refresh_broken_flags(&db_guard, [&mut item]).await
+                        {
+                            error!("Error marking {} as broken: {}", item.id, e);
+                        }
+                        return Err(format!("{} no longer exists", path));
+                    }
+                }
+            }
+        }
+        Ok((paths, restored))
+    }
+
+    // 没有保存格式的旧记录返回 false, 由调用方按类型写入
+    // 缺少 required 格式时不还原, 返回 false
+    async fn set_representations(
+        &self,
+        clipboard_id: i32,
+        required: Option<&str>,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        let representations = {
+            let db_guard = self.db.lock().await;
+            crud::representation::get_representations(&db_guard, clipboard_id).await?
+        };
+        if let Some(required) = required {
+            if !representations.iter().any(|rep| rep.format == required) {
+                return Ok(false);
+            }
+        }
+        let mut clipboard_content = Vec::new();
+        for rep in representations.iter() {
+            if let Some(content) = representation_content(rep)? {
+                clipboard_content.push(content);
+            }
+        }
+        if clipboard_content.is_empty() {
+            return Ok(false);
+        }
+        clipboard_content.push(own_write::marker(&[clipboard_id]));
+        self.ctx.set(clipboard_content)?;
+        Ok(true)
+    }
+
+    async fn get_representations(
+        &self,
+        clipboard_id: i32,
+    ) -> Result<Vec<RepresentationModel>, Box<dyn std::error::Error>> {
+        let db_guard = self.db.lock().await;
+        let representations =
+            crud::representation::get_representations(&db_guard, clipboard_id).await?;
+        Ok(representations)
+    }
+
     async fn set_clipboard(
         &self,
         clipboard: Model,
@@ -119,6 +414,280 @@ impl ClipboardHelper {
         self.set(vec![clipboard]).await
     }
 
+    pub fn capture_state(&self) -> CaptureState {
+        self.capture.get()
+    }
+
+    pub fn set_capture_state(&self, state: CaptureState) -> Result<CaptureState, io::Error> {
+        let state = self.capture.set(state)?;
+        info!("capture state: {:?}", state);
+        Ok(state)
+    }
+
+    pub fn paste_queue_state(&self) -> QueueState {
+        self.paste_queue.lock().state()
+    }
+
+    pub async fn build_paste_queue(
+        &self,
+        id_list: Vec<i32>,
+        mode: QueueMode,
+    ) -> Result<QueueState, String> {
+        let head = self.paste_queue.lock().build(id_list, mode);
+        self.load_queue_head(head).await
+    }
+
+    pub async fn advance_paste_queue(&self) -> Result<QueueState, String> {
+        let head = self.paste_queue.lock().advance();
+        self.load_queue_head(head).await
+    }
+
+    // 等待剪贴板监听中断粘贴队列
+    pub async fn paste_queue_interrupted(&self) {
+        self.paste_queue.interrupted().await
+    }
+
+    // 等待焦点窗口读取队首, 只有 serve_queue_head 提供的条目会触发
+    pub async fn paste_queue_pasted(&self) {
+        self.paste_queue.pasted().await
+    }
+
+    pub fn clear_paste_queue(&self) -> QueueState {
+        let mut queue = self.paste_queue.lock();
+        queue.clear();
+        queue.state()
+    }
+
+    // 把队首条目放到剪贴板, 条目已被删除时跳到下一条
+    async fn load_queue_head(&self, mut head: Option<i32>) -> Result<QueueState, String> {
+        while let Some(id) = head {
+            let entry = {
+                let db_guard = self.db.lock().await;
+                crud::host_clipboard::get_clipboard_entries_by_id_list(&db_guard, Some(vec![id]))
+                    .await
+                    .map_err(|e| e.to_string())?
+                    .into_iter()
+                    .next()
+            };
+            match entry {
+                Some(entry) => {
+                    if !self.serve_queue_head(&entry).await {
+                        self.set(vec![entry]).await?;
+                    }
+                    break;
+                }
+                None => {
+                    debug!("queued entry {} no longer exists", id);
+                    head = self.paste_queue.lock().advance();
+                }
+            }
+        }
+        Ok(self.paste_queue_state())
+    }
+
+    // 由 SuperCV 自己提供队首的文本格式, 被粘贴后自动推进队列;
+    // 文件, 图片和大文本以及不支持的平台返回 false, 仍需快捷键推进
+    async fn serve_queue_head(&self, entry: &Model) -> bool {
+        let text_types = [ContentType::Text.to_i32(), ContentType::RichText.to_i32()];
+        if !text_types.contains(&entry.r#type) || entry.is_large_text() {
+            return false;
+        }
+        let mut targets = Vec::new();
+        match self.get_representations(entry.id).await {
+            Ok(representations) => {
+                for rep in representations.iter() {
+                    match crud::representation::read_data(rep) {
+                        Ok(data) => targets.push((rep.format.clone(), data)),
+                        Err(e) => error!("Error reading representation {}: {}", rep.format, e),
+                    }
+                }
+            }
+            Err(e) => error!("Error getting representations of {}: {}", entry.id, e),
+        }
+        // 没有保存格式的旧记录按字段提供
+        if targets.is_empty() {
+            if let Some(html) = &entry.html {
+                targets.push((FORMAT_HTML.to_string(), html.clone().into_bytes()));
+            }
+            if let Some(rtf) = &entry.rtf {
+                targets.push((FORMAT_RTF.to_string(), rtf.clone().into_bytes()));
+            }
+            targets.push((FORMAT_TEXT.to_string(), entry.content.clone().into_bytes()));
+        }
+        targets.push((
+            own_write::OWN_WRITE_FORMAT.to_string(),
+            own_write::marker_value(&[entry.id]),
+        ));
+
+        let id = entry.id;
+        let paste_queue = self.paste_queue.clone();
+        if !paste_watch::serve(targets, move || paste_queue.notify_pasted(id)) {
+            return false;
+        }
+        self.after_paste(vec![id]).await;
+        true
+    }
+
+    // 粘贴内存中的隐藏内容, 同时带上隐藏标记
+    async fn set_concealed(&self, id: u64) -> Result<(), String> {
+        let content = CONCEALED_STASH
+            .get_content(id)
+            .ok_or_else(|| format!("concealed clipboard {} expired", id))?;
+        let mut clipboard_content = vec![ClipboardContent::Text(content)];
+        clipboard_content.extend(concealed::concealed_markers());
+        // 粘贴的是已保存的内容, 不再重新保存或刷新到期时间
+        clipboard_content.push(own_write::marker(&[]));
+        self.ctx.set(clipboard_content)
+            .map_err(|e| {
+                error!("Error setting concealed clipboard: {}", e);
+                e.to_string()
+            })
+    }
+
+    async fn set_pinned(&self, id: i32, pinned: bool) -> Result<Model, Box<dyn std::error::Error>> {
+        let db_guard = self.db.lock().await;
+        let entry = crud::host_clipboard::set_clipboard_pinned(&db_guard, id, pinned).await?;
+        Ok(entry)
+    }
+
+    async fn get_pinned_clipboards(&self) -> Result<Vec<Model>, Box<dyn std::error::Error>> {
+        let mut entries = {
+            let db_guard = self.db.lock().await;
+            crud::host_clipboard::get_pinned_clipboards(&db_guard).await?
+        };
+        self.refresh_broken_flags(entries.iter_mut()).await?;
+        Ok(entries)
+    }
+
+    async fn get_clipboard_versions(&self, id: i32) -> Result<Vec<Model>, Box<dyn std::error::Error>> {
+        let mut entries = {
+            let db_guard = self.db.lock().await;
+            crud::host_clipboard::get_clipboard_versions(&db_guard, id).await?
+        };
+        self.refresh_broken_flags(entries.iter_mut()).await?;
+        Ok(entries)
+    }
+
+    async fn reorder_pinned_clipboards(
+        &self,
+        id_list: Vec<i32>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let db_guard = self.db.lock().await;
+        crud::host_clipboard::reorder_pinned_clipboards(&db_guard, id_list).await?;
+        Ok(())
+    }
+
+    async fn get_tags(
+        &self,
+        clipboard_id: Option<i32>,
+    ) -> Result<Vec<TagModel>, Box<dyn std::error::Error>> {
+        let db_guard = self.db.lock().await;
+        let tags = match clipboard_id {
+            Some(clipboard_id) => crud::tag::get_tags_by_clipboard(&db_guard, clipboard_id).await?,
+            None => crud::tag::get_tags(&db_guard).await?,
+        };
+        Ok(tags)
+    }
+
+    async fn create_tag(&self, name: &str) -> Result<TagModel, Box<dyn std::error::Error>> {
+        let db_guard = self.db.lock().await;
+        let tag = crud::tag::create_tag(&db_guard, name).await?;
+        Ok(tag)
+    }
+
+    async fn rename_tag(
+        &self,
+        id: i32,
+        name: &str,
+    ) -> Result<TagModel, Box<dyn std::error::Error>> {
+        let db_guard = self.db.lock().await;
+        let tag = crud::tag::rename_tag(&db_guard, id, name).await?;
+        Ok(tag)
+    }
+
+    async fn delete_tag(&self, id: i32) -> Result<(), Box<dyn std::error::Error>> {
+        let db_guard = self.db.lock().await;
+        crud::tag::delete_tag(&db_guard, id).await?;
+        Ok(())
+    }
+
+    async fn set_tag(
+        &self,
+        clipboard_id: i32,
+        tag_id: i32,
+        attached: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let db_guard = self.db.lock().await;
+        if attached {
+            crud::tag::attach_tag(&db_guard, clipboard_id, tag_id).await?;
+        } else {
+            crud::tag::detach_tag(&db_guard, clipboard_id, tag_id).await?;
+        }
+        Ok(())
+    }
+
+    // 将 supercv:// 地址解析为磁盘文件, 是否允许访问由调用方检查;
+    // 在主线程中调用, 数据库锁只用于查询, 缩略图正在生成时返回 None
+    pub async fn resolve_resource(&self, route: &Route) -> Result<Option<Resource>, Box<dyn std::error::Error>> {
+        match route {
+            Route::Blob(hash) => {
+                if let Some(path) = BLOB_STORE.lookup(hash, IMG_BLOB_EXT) {
+                    return Ok(Some(Resource {
+                        path,
+                        mimetype: FORMAT_PNG.to_string(),
+                        etag: hash.clone(),
+                    }));
+                }
+                let path = BLOB_STORE
+                    .lookup(hash, REPRESENTATION_BLOB_EXT)
+                    .ok_or_else(|| format!("blob {} not found", hash))?;
+                // 格式内容使用复制时的 MIME 类型
+                let format = {
+                    let db_guard = self.db.lock().await;
+                    crud::representation::get_format_by_path(&db_guard, path.to_str().unwrap()).await?
+                };
+                let mimetype = format
+                    .filter(|format| format.contains('/'))
+                    .unwrap_or_else(|| "application/octet-stream".to_string());
+                Ok(Some(Resource {
+                    path,
+                    mimetype,
+                    etag: hash.clone(),
+                }))
+            }
+            // 旧记录没有缩略图或尺寸已变化时在后台生成, 不在主线程中解码图片
+            Route::Thumb(id) => {
+                let entry = {
+                    let db_guard = self.db.lock().await;
+                    crud::host_clipboard::get_clipboard_entries_by_id_list(&db_guard, Some(vec![*id]))
+                        .await?
+                        .pop()
+                        .ok_or_else(|| format!("clipboard {} not found", id))?
+                };
+                if entry.r#type != ContentType::Image.to_i32() {
+                    return Err(format!("clipboard {} is not an image", id).into());
+                }
+                let source = item_paths(&entry).pop().unwrap_or_default();
+                let Some(path) = thumbnail::get_current(&entry.hash, &source) else {
+                    thumbnail::create_in_background(entry.hash, source);
+                    return Ok(None);
+                };
+                let size = CONFIG.read().unwrap().user_config.preview_config.thumbnail_size;
+                Ok(Some(Resource {
+                    path,
+                    mimetype: "image/jpeg".to_string(),
+                    etag: format!("{}-{}", entry.hash, size),
+                }))
+            }
+        }
+    }
+
+    async fn purge_expired(&self) -> Result<PurgeReport, Box<dyn std::error::Error>> {
+        let db_guard = self.db.lock().await;
+        let report = time_it!(async retention::purge_expired(&db_guard)).await?;
+        Ok(report)
+    }
+
     pub async fn get_user_config() -> UserConfig {
         CONFIG.read().unwrap().user_config.clone()
     }
@@ -126,14 +695,107 @@ impl ClipboardHelper {
     pub async fn set_user_config(user_config: UserConfig) -> io::Result<()> {
         config::update(user_config).await
     }
+
+    pub fn get_ignore_errors() -> Vec<String> {
+        CONFIG.read().unwrap().ignore_errors.clone()
+    }
+}
+// 搜索失败时返回给前端的结构化错误
+#[derive(Serialize, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SearchError {
+    Parse(QueryError),
+    Database { message: String },
 }
+
+// 大文本从 blob store 中读取全文
+fn full_text(item: Model) -> io::Result<String> {
+    if item.is_large_text() {
+        large_text::load(&item.path)
+    } else {
+        Ok(item.content)
+    }
+}
+
+fn rich_text_contents(item: Model) -> Vec<ClipboardContent> {
+    let mut contents = Vec::new();
+    if let Some(html) = item.html {
+        contents.push(ClipboardContent::Html(html));
+    }
+    if let Some(rtf) = item.rtf {
+        contents.push(ClipboardContent::Rtf(rtf));
+    }
+    contents.push(ClipboardContent::Text(item.content));
+    contents
+}
+
+// 常见格式转换为对应的剪贴板内容, 其余格式按原始字节写回
+fn representation_content(
+    rep: &RepresentationModel,
+) -> Result<Option<ClipboardContent>, Box<dyn std::error::Error>> {
+    let data = crud::representation::read_data(rep)?;
+    let content = match rep.format.as_str() {
+        FORMAT_TEXT => ClipboardContent::Text(String::from_utf8_lossy(&data).into_owned()),
+        FORMAT_HTML => ClipboardContent::Html(String::from_utf8_lossy(&data).into_owned()),
+        FORMAT_RTF => ClipboardContent::Rtf(String::from_utf8_lossy(&data).into_owned()),
+        FORMAT_URI_LIST => {
+            let paths: Vec<String> = String::from_utf8_lossy(&data)
+                .lines()
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .filter_map(|line| url::Url::parse(line).ok()?.to_file_path().ok())
+                .map(|path| path.to_string_lossy().into_owned())
+                .collect();
+            if paths.is_empty() {
+                return Ok(None);
+            }
+            ClipboardContent::Files(paths)
+        }
+        FORMAT_PNG => ClipboardContent::Image(RustImageData::from_bytes(&data)?),
+        format => ClipboardContent::Other(format.to_string(), data),
+    };
+    Ok(Some(content))
+}
+
+// 图片以位图粘贴; 用户复制的图片文件或按配置同时提供文件路径
+fn image_contents(paths: Vec<String>) -> Result<Vec<ClipboardContent>, Box<dyn std::error::Error>> {
+    let image = RustImageData::from_path(&paths[0])?;
+    let paste_file_uri = CONFIG.read().unwrap().user_config.image_config.paste_file_uri;
+    let mut contents = vec![ClipboardContent::Image(image)];
+    if paste_file_uri || !BLOB_STORE.contains(&paths[0]) {
+        contents.push(ClipboardContent::Files(paths));
+    }
+    Ok(contents)
+}
+
+// 截图类图片条目从 blob store 中取文件, 其余条目直接使用记录的路径
+fn item_paths(item: &Model) -> Vec<String> {
+    if item.r#type == ContentType::Image.to_i32() {
+        if let Some(path) = BLOB_STORE.lookup(&item.hash, IMG_BLOB_EXT) {
+            return vec![path.to_str().unwrap().to_string()];
+        }
+    }
+    item.paths()
+}
+
 #[tauri::command]
 pub async fn rs_invoke_get_clipboards(
     state: tauri::State<'_, Arc<ClipboardHelper>>,
     num: u64,
     type_list: Option<Vec<i32>>,
+    tag_list: Option<Vec<i32>>,
+    subtype_list: Option<Vec<i32>>,
+    group_versions: Option<bool>,
 ) -> Result<Vec<Model>, String> {
-    match state.get_clipboards(num, type_list).await {
+    match state
+        .get_clipboards(
+            num,
+            type_list,
+            tag_list,
+            subtype_list,
+            group_versions.unwrap_or(false),
+        )
+        .await
+    {
         Ok(clipboards) => Ok(clipboards),
         Err(e) => {
             error!("rs_invoke_get_clipboards err: {:?}", e);
@@ -148,12 +810,13 @@ pub async fn rs_invoke_search_clipboards(
     query: &str,
     num: u64,
     type_list: Option<Vec<i32>>,
-) -> Result<Vec<Model>, String> {
-    match state.search_clipboards(query, num, type_list).await {
+    tag_list: Option<Vec<i32>>,
+) -> Result<Vec<SearchHit>, SearchError> {
+    match state.search_clipboards(query, num, type_list, tag_list).await {
         Ok(clipboards) => Ok(clipboards),
         Err(e) => {
             error!("rs_invoke_search_clipboards err: {:?}", e);
-            Err(format!("Failed to search clipboards: {}", e))
+            Err(e)
         }
     }
 }
@@ -172,6 +835,304 @@ pub async fn rs_invoke_set_clipboards(
     }
 }
 
+#[tauri::command]
+pub async fn rs_invoke_set_clipboards_transformed(
+    state: tauri::State<'_, Arc<ClipboardHelper>>,
+    item: Model,
+    transforms: Vec<Transform>,
+) -> Result<bool, String> {
+    match state.set_transformed(item, transforms).await {
+        Ok(_) => Ok(true),
+        Err(e) => {
+            error!("rs_invoke_set_clipboards_transformed err: {:?}", e);
+            Err(format!("Failed to set transformed clipboard: {}", e))
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn rs_invoke_set_clipboards_joined(
+    state: tauri::State<'_, Arc<ClipboardHelper>>,
+    items: Vec<Model>,
+    options: JoinOptions,
+) -> Result<bool, String> {
+    match state.set_joined(items, options).await {
+        Ok(_) => Ok(true),
+        Err(e) => {
+            error!("rs_invoke_set_clipboards_joined err: {:?}", e);
+            Err(format!("Failed to set joined clipboard: {}", e))
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn rs_invoke_get_representations(
+    state: tauri::State<'_, Arc<ClipboardHelper>>,
+    clipboard_id: i32,
+) -> Result<Vec<RepresentationModel>, String> {
+    match state.get_representations(clipboard_id).await {
+        Ok(representations) => Ok(representations),
+        Err(e) => {
+            error!("rs_invoke_get_representations err: {:?}", e);
+            Err(format!("Failed to get representations: {}", e))
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn rs_invoke_pin_clipboard(
+    state: tauri::State<'_, Arc<ClipboardHelper>>,
+    id: i32,
+) -> Result<Model, String> {
+    match state.set_pinned(id, true).await {
+        Ok(entry) => Ok(entry),
+        Err(e) => {
+            error!("rs_invoke_pin_clipboard err: {:?}", e);
+            Err(format!("Failed to pin clipboard: {}", e))
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn rs_invoke_unpin_clipboard(
+    state: tauri::State<'_, Arc<ClipboardHelper>>,
+    id: i32,
+) -> Result<Model, String> {
+    match state.set_pinned(id, false).await {
+        Ok(entry) => Ok(entry),
+        Err(e) => {
+            error!("rs_invoke_unpin_clipboard err: {:?}", e);
+            Err(format!("Failed to unpin clipboard: {}", e))
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn rs_invoke_get_pinned_clipboards(
+    state: tauri::State<'_, Arc<ClipboardHelper>>,
+) -> Result<Vec<Model>, String> {
+    match state.get_pinned_clipboards().await {
+        Ok(clipboards) => Ok(clipboards),
+        Err(e) => {
+            error!("rs_invoke_get_pinned_clipboards err: {:?}", e);
+            Err(format!("Failed to get pinned clipboards: {}", e))
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn rs_invoke_get_clipboard_versions(
+    state: tauri::State<'_, Arc<ClipboardHelper>>,
+    id: i32,
+) -> Result<Vec<Model>, String> {
+    match state.get_clipboard_versions(id).await {
+        Ok(clipboards) => Ok(clipboards),
+        Err(e) => {
+            error!("rs_invoke_get_clipboard_versions err: {:?}", e);
+            Err(format!("Failed to get clipboard versions: {}", e))
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn rs_invoke_reorder_pinned_clipboards(
+    state: tauri::State<'_, Arc<ClipboardHelper>>,
+    id_list: Vec<i32>,
+) -> Result<bool, String> {
+    match state.reorder_pinned_clipboards(id_list).await {
+        Ok(()) => Ok(true),
+        Err(e) => {
+            error!("rs_invoke_reorder_pinned_clipboards err: {:?}", e);
+            Err(format!("Failed to reorder pinned clipboards: {}", e))
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn rs_invoke_get_tags(
+    state: tauri::State<'_, Arc<ClipboardHelper>>,
+    clipboard_id: Option<i32>,
+) -> Result<Vec<TagModel>, String> {
+    match state.get_tags(clipboard_id).await {
+        Ok(tags) => Ok(tags),
+        Err(e) => {
+            error!("rs_invoke_get_tags err: {:?}", e);
+            Err(format!("Failed to get tags: {}", e))
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn rs_invoke_create_tag(
+    state: tauri::State<'_, Arc<ClipboardHelper>>,
+    name: &str,
+) -> Result<TagModel, String> {
+    match state.create_tag(name).await {
+        Ok(tag) => Ok(tag),
+        Err(e) => {
+            error!("rs_invoke_create_tag err: {:?}", e);
+            Err(format!("Failed to create tag: {}", e))
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn rs_invoke_rename_tag(
+    state: tauri::State<'_, Arc<ClipboardHelper>>,
+    id: i32,
+    name: &str,
+) -> Result<TagModel, String> {
+    match state.rename_tag(id, name).await {
+        Ok(tag) => Ok(tag),
+        Err(e) => {
+            error!("rs_invoke_rename_tag err: {:?}", e);
+            Err(format!("Failed to rename tag: {}", e))
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn rs_invoke_delete_tag(
+    state: tauri::State<'_, Arc<ClipboardHelper>>,
+    id: i32,
+) -> Result<bool, String> {
+    match state.delete_tag(id).await {
+        Ok(()) => Ok(true),
+        Err(e) => {
+            error!("rs_invoke_delete_tag err: {:?}", e);
+            Err(format!("Failed to delete tag: {}", e))
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn rs_invoke_attach_tag(
+    state: tauri::State<'_, Arc<ClipboardHelper>>,
+    clipboard_id: i32,
+    tag_id: i32,
+) -> Result<bool, String> {
+    match state.set_tag(clipboard_id, tag_id, true).await {
+        Ok(()) => Ok(true),
+        Err(e) => {
+            error!("rs_invoke_attach_tag err: {:?}", e);
+            Err(format!("Failed to attach tag: {}", e))
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn rs_invoke_detach_tag(
+    state: tauri::State<'_, Arc<ClipboardHelper>>,
+    clipboard_id: i32,
+    tag_id: i32,
+) -> Result<bool, String> {
+    match state.set_tag(clipboard_id, tag_id, false).await {
+        Ok(()) => Ok(true),
+        Err(e) => {
+            error!("rs_invoke_detach_tag err: {:?}", e);
+            Err(format!("Failed to detach tag: {}", e))
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn rs_invoke_get_concealed_clipboards(
+    _: tauri::State<'_, Arc<ClipboardHelper>>,
+) -> Result<Vec<ConcealedClip>, String> {
+    Ok(CONCEALED_STASH.list())
+}
+
+#[tauri::command]
+pub async fn rs_invoke_set_concealed_clipboard(
+    state: tauri::State<'_, Arc<ClipboardHelper>>,
+    id: u64,
+) -> Result<bool, String> {
+    match state.set_concealed(id).await {
+        Ok(()) => Ok(true),
+        Err(e) => {
+            error!("rs_invoke_set_concealed_clipboard err: {:?}", e);
+            Err(format!("Failed to set concealed clipboard: {}", e))
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn rs_invoke_clear_concealed_clipboards(
+    _: tauri::State<'_, Arc<ClipboardHelper>>,
+) -> Result<bool, String> {
+    CONCEALED_STASH.clear();
+    Ok(true)
+}
+
+#[tauri::command]
+pub async fn rs_invoke_get_capture_state(
+    state: tauri::State<'_, Arc<ClipboardHelper>>,
+) -> Result<CaptureState, String> {
+    Ok(state.capture_state())
+}
+
+// minutes 为空时一直暂停, 直到手动恢复
+#[tauri::command]
+pub async fn rs_invoke_pause_capture(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, Arc<ClipboardHelper>>,
+    minutes: Option<i64>,
+) -> Result<CaptureState, String> {
+    let capture_state = match minutes {
+        Some(minutes) if minutes <= 0 => {
+            return Err(format!("Invalid pause duration: {} minutes", minutes));
+        }
+        Some(minutes) => {
+            let until = minutes
+                .checked_mul(60)
+                .and_then(|secs| get_current_timestamp().checked_add(secs))
+                .ok_or_else(|| format!("Pause duration too long: {} minutes", minutes))?;
+            CaptureState::PausedUntil { until }
+        }
+        None => CaptureState::Paused,
+    };
+    match state.set_capture_state(capture_state) {
+        Ok(capture_state) => {
+            crate::sync_capture_tray(&app_handle, capture_state);
+            Ok(capture_state)
+        }
+        Err(e) => {
+            error!("rs_invoke_pause_capture err: {:?}", e);
+            Err(format!("Failed to pause capture: {}", e))
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn rs_invoke_resume_capture(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, Arc<ClipboardHelper>>,
+) -> Result<CaptureState, String> {
+    match state.set_capture_state(CaptureState::Running) {
+        Ok(capture_state) => {
+            crate::sync_capture_tray(&app_handle, capture_state);
+            Ok(capture_state)
+        }
+        Err(e) => {
+            error!("rs_invoke_resume_capture err: {:?}", e);
+            Err(format!("Failed to resume capture: {}", e))
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn rs_invoke_purge_expired(
+    state: tauri::State<'_, Arc<ClipboardHelper>>,
+) -> Result<PurgeReport, String> {
+    match state.purge_expired().await {
+        Ok(report) => Ok(report),
+        Err(e) => {
+            error!("rs_invoke_purge_expired err: {:?}", e);
+            Err(format!("Failed to purge expired clipboards: {}", e))
+        }
+    }
+}
+
 #[tauri::command]
 pub async fn rs_invoke_get_user_config(
     _: tauri::State<'_, Arc<ClipboardHelper>>,
@@ -194,3 +1155,64 @@ pub async fn rs_invoke_set_user_config(
         }
     }
 }
+
+#[tauri::command]
+pub async fn rs_invoke_get_ignore_errors(
+    _: tauri::State<'_, Arc<ClipboardHelper>>,
+) -> Result<Vec<String>, String> {
+    Ok(ClipboardHelper::get_ignore_errors())
+}
+
+#[tauri::command]
+pub async fn rs_invoke_get_paste_queue(
+    state: tauri::State<'_, Arc<ClipboardHelper>>,
+) -> Result<QueueState, String> {
+    Ok(state.paste_queue_state())
+}
+
+#[tauri::command]
+pub async fn rs_invoke_build_paste_queue(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, Arc<ClipboardHelper>>,
+    id_list: Vec<i32>,
+    mode: Option<QueueMode>,
+) -> Result<QueueState, String> {
+    let result = state
+        .build_paste_queue(id_list, mode.unwrap_or_default())
+        .await;
+    // 队首条目无法粘贴时队列仍已建立, 同样通知前端
+    crate::sync_paste_queue(&app_handle, state.paste_queue_state());
+    match result {
+        Ok(queue_state) => Ok(queue_state),
+        Err(e) => {
+            error!("rs_invoke_build_paste_queue err: {:?}", e);
+            Err(format!("Failed to build paste queue: {}", e))
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn rs_invoke_advance_paste_queue(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, Arc<ClipboardHelper>>,
+) -> Result<QueueState, String> {
+    let result = state.advance_paste_queue().await;
+    crate::sync_paste_queue(&app_handle, state.paste_queue_state());
+    match result {
+        Ok(queue_state) => Ok(queue_state),
+        Err(e) => {
+            error!("rs_invoke_advance_paste_queue err: {:?}", e);
+            Err(format!("Failed to advance paste queue: {}", e))
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn rs_invoke_clear_paste_queue(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, Arc<ClipboardHelper>>,
+) -> Result<QueueState, String> {
+    let queue_state = state.clear_paste_queue();
+    crate::sync_paste_queue(&app_handle, queue_state.clone());
+    Ok(queue_state)
+}
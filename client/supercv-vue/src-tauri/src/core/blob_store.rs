@@ -0,0 +1,211 @@
+use crate::utils::config::CONFIG;
+use lazy_static::lazy_static;
+use log::debug;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+lazy_static! {
+    pub static ref BLOB_STORE: BlobStore =
+        BlobStore::new(CONFIG.read().unwrap().files_path.join("blobs"));
+}
+
+pub const IMG_BLOB_EXT: &str = "png";
+
+// 以内容 hash 为键的文件存储, 相同内容只会写入一次
+// 目录结构: blobs/<hash 前两位>/<hash>.<ext>
+#[derive(Debug)]
+pub struct BlobStore {
+    root: PathBuf,
+    // 正在写入或已写入但还没有记录引用的 blob, 清理时跳过这些文件和所在目录
+    pins: Mutex<HashMap<PathBuf, usize>>,
+}
+
+// 持有期间 blob 不会被清理, 记录入库后再释放
+#[derive(Debug)]
+pub struct BlobPin {
+    store: &'static BlobStore,
+    path: PathBuf,
+}
+
+impl BlobPin {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Clone for BlobPin {
+    fn clone(&self) -> Self {
+        self.store.pin(self.path.clone())
+    }
+}
+
+impl Drop for BlobPin {
+    fn drop(&mut self) {
+        let mut pins = self.store.pins.lock().unwrap();
+        if let Some(count) = pins.get_mut(&self.path) {
+            *count -= 1;
+            if *count == 0 {
+                pins.remove(&self.path);
+            }
+        }
+    }
+}
+
+impl BlobStore {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            pins: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn path_of(&self, hash: &str, ext: &str) -> PathBuf {
+        let shard = &hash[..hash.len().min(2)];
+        self.root.join(shard).join(format!("{}.{}", hash, ext))
+    }
+
+    // 查找已存储的 blob
+    pub fn lookup(&self, hash: &str, ext: &str) -> Option<PathBuf> {
+        let path = self.path_of(hash, ext);
+        if path.is_file() {
+            Some(path)
+        } else {
+            None
+        }
+    }
+
+    // 判断路径是否由 blob store 管理
+    pub fn contains<P: AsRef<Path>>(&self, path: P) -> bool {
+        path.as_ref().starts_with(&self.root)
+    }
+
+    fn pin(&'static self, path: PathBuf) -> BlobPin {
+        *self.pins.lock().unwrap().entry(path.clone()).or_insert(0) += 1;
+        BlobPin { store: self, path }
+    }
+
+    pub fn put(&'static self, hash: &str, ext: &str, bytes: &[u8]) -> io::Result<PathBuf> {
+        self.put_pinned(hash, ext, bytes)
+            .map(|pin| pin.path().to_path_buf())
+    }
+
+    pub fn put_with<F>(&'static self, hash: &str, ext: &str, write: F) -> io::Result<PathBuf>
+    where
+        F: FnOnce(&Path) -> io::Result<()>,
+    {
+        self.put_with_pinned(hash, ext, write)
+            .map(|pin| pin.path().to_path_buf())
+    }
+
+    pub fn put_pinned(&'static self, hash: &str, ext: &str, bytes: &[u8]) -> io::Result<BlobPin> {
+        self.put_with_pinned(hash, ext, |tmp| fs::write(tmp, bytes))
+    }
+
+    // 通过 write 写入临时文件, 写完后 rename 到最终路径; blob 已存在时直接返回.
+    // 写入前先 pin, 清理不会删掉正在写入的分片目录, 也不会删掉刚被再次复制的 blob
+    pub fn put_with_pinned<F>(&'static self, hash: &str, ext: &str, write: F) -> io::Result<BlobPin>
+    where
+        F: FnOnce(&Path) -> io::Result<()>,
+    {
+        let path = self.path_of(hash, ext);
+        let pin = self.pin(path.clone());
+        if path.is_file() {
+            debug!("blob {} already stored", hash);
+            return Ok(pin);
+        }
+
+        let dir = path.parent().unwrap();
+        fs::create_dir_all(dir)?;
+        // 临时文件保留扩展名, 部分写入方式依赖扩展名推断格式
+        let tmp = dir.join(format!(
+            ".{}.{}.{}",
+            hash,
+            uuid::Uuid::new_v4().simple(),
+            ext
+        ));
+        if let Err(e) = write(&tmp).and_then(|_| fs::rename(&tmp, &path)) {
+            let _ = fs::remove_file(&tmp);
+            return Err(e);
+        }
+        Ok(pin)
+    }
+
+    // 删除 blob 并返回释放的字节数, 不处理 blob store 之外的路径, 被 pin 的 blob 保留
+    pub fn remove<P: AsRef<Path>>(&self, path: P) -> io::Result<u64> {
+        let path = path.as_ref();
+        if !self.contains(path) {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("{} is outside the blob store", path.display()),
+            ));
+        }
+        let pins = self.pins.lock().unwrap();
+        if pins.contains_key(path) {
+            debug!("blob {} is pinned, skip removing", path.display());
+            return Ok(0);
+        }
+        let size = fs::metadata(path)?.len();
+        fs::remove_file(path)?;
+        // 分片目录为空时一并删除, 非空时删除失败可以忽略
+        if let Some(dir) = path.parent() {
+            if !is_pinned_dir(&pins, dir) {
+                let _ = fs::remove_dir(dir);
+            }
+        }
+        Ok(size)
+    }
+
+    // 删除空目录, 其中有 blob 正在写入时保留, 返回是否删除
+    pub fn remove_empty_dir(&self, dir: &Path) -> io::Result<bool> {
+        let pins = self.pins.lock().unwrap();
+        if is_pinned_dir(&pins, dir) || fs::read_dir(dir)?.next().is_some() {
+            return Ok(false);
+        }
+        fs::remove_dir(dir)?;
+        Ok(true)
+    }
+}
+
+fn is_pinned_dir(pins: &HashMap<PathBuf, usize>, dir: &Path) -> bool {
+    pins.keys().any(|path| path.starts_with(dir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pinned_blob_is_kept() {
+        let root = std::env::temp_dir().join(format!("super-cv-blobs-{}", std::process::id()));
+        let store: &'static BlobStore = Box::leak(Box::new(BlobStore::new(root.clone())));
+
+        let pin = store.put_pinned("abcd", "bin", b"data").unwrap();
+        let path = pin.path().to_path_buf();
+        let shard = path.parent().unwrap().to_path_buf();
+        assert_eq!(store.remove(&path).unwrap(), 0);
+        assert!(path.is_file());
+
+        // 再次写入相同内容时复用并重新 pin
+        let again = store.put_pinned("abcd", "bin", b"data").unwrap();
+        drop(pin);
+        assert_eq!(store.remove(&path).unwrap(), 0);
+        drop(again);
+        assert_eq!(store.remove(&path).unwrap(), 4);
+        assert!(!shard.exists());
+
+        // 分片目录已创建但 blob 还没写入时不会被清理
+        fs::create_dir_all(&shard).unwrap();
+        let pending = store.pin(shard.join("abcd.bin"));
+        assert!(!store.remove_empty_dir(&shard).unwrap());
+        drop(pending);
+        assert!(store.remove_empty_dir(&shard).unwrap());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,131 @@
+use crate::utils::time::get_current_timestamp;
+use log::error;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+// 剪贴板记录状态
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum CaptureState {
+    Running,
+    Paused,
+    PausedUntil { until: i64 }, // 到期时间戳, 到期后自动恢复
+}
+
+impl CaptureState {
+    // 暂停到期后回到 Running
+    pub fn resolve(self, now: i64) -> Self {
+        match self {
+            CaptureState::PausedUntil { until } if until <= now => CaptureState::Running,
+            state => state,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        *self == CaptureState::Running
+    }
+}
+
+// 记录状态保存在配置目录中, 重启或崩溃后不会自动恢复记录
+pub struct CaptureControl {
+    state: Mutex<CaptureState>,
+    path: PathBuf,
+}
+
+impl CaptureControl {
+    pub fn load(path: PathBuf) -> Self {
+        let state = match fs::read_to_string(&path) {
+            Ok(content) => toml::from_str(&content).unwrap_or_else(|e| {
+                // 状态文件损坏时保持暂停, 由用户手动恢复
+                error!("Failed to parse {}: {}", path.display(), e);
+                CaptureState::Paused
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => CaptureState::Running,
+            Err(e) => {
+                error!("Failed to read {}: {}", path.display(), e);
+                CaptureState::Paused
+            }
+        };
+        Self {
+            state: Mutex::new(state),
+            path,
+        }
+    }
+
+    pub fn get(&self) -> CaptureState {
+        let mut state = self.state.lock().unwrap();
+        let resolved = state.resolve(get_current_timestamp());
+        if resolved != *state {
+            *state = resolved;
+            if let Err(e) = self.save(resolved) {
+                error!("Failed to save capture state: {}", e);
+            }
+        }
+        resolved
+    }
+
+    pub fn set(&self, new_state: CaptureState) -> io::Result<CaptureState> {
+        let mut state = self.state.lock().unwrap();
+        let new_state = new_state.resolve(get_current_timestamp());
+        // 先落盘再生效, 保存失败时保持原状态
+        self.save(new_state)?;
+        *state = new_state;
+        Ok(new_state)
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.get().is_running()
+    }
+
+    fn save(&self, state: CaptureState) -> io::Result<()> {
+        let content =
+            toml::to_string(&state).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let tmp = self.path.with_extension("toml.tmp");
+        fs::write(&tmp, content)?;
+        fs::rename(&tmp, &self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        let paused_until = CaptureState::PausedUntil { until: 100 };
+        assert_eq!(paused_until.resolve(99), paused_until);
+        assert_eq!(paused_until.resolve(100), CaptureState::Running);
+        assert_eq!(CaptureState::Paused.resolve(i64::MAX), CaptureState::Paused);
+    }
+
+    #[test]
+    fn test_persist() {
+        let dir = std::env::temp_dir().join(format!("supercv-capture-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("capture.toml");
+
+        assert!(CaptureControl::load(path.clone()).is_running());
+
+        let until = get_current_timestamp() + 600;
+        let control = CaptureControl::load(path.clone());
+        control.set(CaptureState::PausedUntil { until }).unwrap();
+        assert_eq!(
+            CaptureControl::load(path.clone()).get(),
+            CaptureState::PausedUntil { until }
+        );
+
+        control.set(CaptureState::PausedUntil { until: 0 }).unwrap();
+        assert!(CaptureControl::load(path.clone()).is_running());
+
+        fs::write(&path, "state = \"???\"").unwrap();
+        assert_eq!(
+            CaptureControl::load(path.clone()).get(),
+            CaptureState::Paused
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,5 +1,14 @@
 use std::error::Error;
-use crate::core::pasteboard::{ContentType, PasteboardContent};
+use crate::core::blob_store::BLOB_STORE;
+use crate::core::capture::CaptureControl;
+use crate::core::concealed::{self, CONCEALED_STASH};
+use crate::core::own_write;
+use crate::core::paste_queue::SharedPasteQueue;
+use crate::core::pasteboard::{ContentType, PasteboardContent, Representation, FORMAT_PNG};
+use crate::core::source_app;
+use crate::core::snapshot::{self, Snapshot};
+use crate::core::thumbnail;
+use crate::db::crud;
 use crate::db::crud::host_clipboard::add_clipboard_entry;
 use crate::utils::config::CONFIG;
 use crate::utils::file::{format_size, get_file_size};
@@ -13,6 +22,7 @@ use log::{debug, error};
 use sea_orm::DatabaseConnection;
 use std::io;
 use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::sync::Arc;
 use std::thread::JoinHandle;
@@ -27,21 +37,37 @@ pub struct ClipboardHandle {
     db: Arc<Mutex<DatabaseConnection>>,
     ctx: ClipboardContext,
     pub(crate) last_hash: String,
-    sender: Sender<PasteboardContent>,
+    capture: Arc<CaptureControl>,
+    paste_queue: Arc<SharedPasteQueue>,
+    generation: Arc<AtomicU64>, // 剪贴板变化的次数, 后台线程读取格式后据此确认剪贴板没有再变化
+    sender: Sender<(PasteboardContent, Option<PendingFormats>)>,
     receiver_handle: JoinHandle<()>,
     runtime: Arc<Runtime>,
 }
 
+// 监听线程中只取格式列表, 格式内容在后台线程中读取
+struct PendingFormats {
+    formats: Vec<String>,
+    generation: u64,
+}
+
 impl ClipboardHandle {
-    pub fn new(db: Arc<Mutex<DatabaseConnection>>) -> Self {
+    pub fn new(
+        db: Arc<Mutex<DatabaseConnection>>,
+        capture: Arc<CaptureControl>,
+        paste_queue: Arc<SharedPasteQueue>,
+    ) -> Self {
         let ctx = ClipboardContext::new().unwrap();
         let (sender, receiver) = mpsc::channel();
         let runtime = Arc::new(Runtime::new().unwrap());
 
+        let generation = Arc::new(AtomicU64::new(0));
+
         let db_clone = db.clone();
         let runtime_clone = runtime.clone();
+        let generation_clone = generation.clone();
         let receiver_handle = std::thread::spawn(move || {
-            Self::process_receiver(receiver, db_clone, runtime_clone);
+            Self::process_receiver(receiver, db_clone, runtime_clone, generation_clone);
         });
 
         ClipboardHandle {
@@ -49,16 +75,46 @@ impl ClipboardHandle {
             db,
             sender,
             last_hash: "".to_string(),
+            capture,
+            paste_queue,
+            generation,
             receiver_handle,
             runtime,
         }
     }
     fn process_receiver(
-        receiver: Receiver<PasteboardContent>,
+        receiver: Receiver<(PasteboardContent, Option<PendingFormats>)>,
         db: Arc<Mutex<DatabaseConnection>>,
         runtime: Arc<Runtime>,
+        generation: Arc<AtomicU64>,
     ) {
-        while let Ok(content) = receiver.recv() {
+        let ctx = ClipboardContext::new().unwrap();
+        while let Ok((mut content, pending)) = receiver.recv() {
+            // 读取失败或剪贴板已变化时为空, 入库时根据记录内容生成
+            if let Some(pending) = pending {
+                content.representations = Self::read_representations(&ctx, &generation, pending);
+            }
+            // 缩略图以 hash 为键, 需要先确定文件条目的 hash
+            content.hash_file_versions();
+            // 在后台线程中压缩, 不阻塞剪贴板监听
+            if let Err(e) = content.store_large_text() {
+                error!("Failed to store large text: {}", e);
+                continue;
+            }
+            // 缩略图失败不影响记录, 展示时会重新生成
+            if content.r#type == ContentType::Image {
+                if let Err(e) = thumbnail::get_or_create(&content.hash, &content.path) {
+                    error!("Failed to create thumbnail for {}: {}", content.path, e);
+                }
+            }
+            // 统计失败时仍然记录, 只是没有预览
+            if let Err(e) = content.summarize_directory() {
+                error!("Failed to summarize directory {}: {}", content.path, e);
+            }
+            if let Err(e) = content.measure_directories() {
+                error!("Failed to measure directories in {}: {}", content.path, e);
+            }
+            content.store_representations();
             // debug!("Received clipboard content: {:?}", content);
             runtime.block_on(async {
                 Self::add_clipboard_entry(&db, content).await;
@@ -66,23 +122,105 @@ impl ClipboardHandle {
         }
     }
 
-    async fn add_clipboard_entry(db: &Arc<Mutex<DatabaseConnection>>, content: PasteboardContent) {
+    async fn add_clipboard_entry(
+        db: &Arc<Mutex<DatabaseConnection>>,
+        mut content: PasteboardContent,
+    ) {
+        // blob 都在拿数据库锁之前写入, 持有 pin 直到记录入库, 期间清理不会删除这些 blob
+        let _pins = std::mem::take(&mut content.pins);
+        let snapshots = Self::take_snapshots(&content);
         let db_guard = db.lock().await;
-        time_it!(async add_clipboard_entry(&db_guard, content))
+        let entry = time_it!(async add_clipboard_entry(&db_guard, content))
             .await
             .unwrap();
+        if snapshots.is_empty() {
+            return;
+        }
+        match crud::file_snapshot::add_snapshots(&db_guard, entry.id, snapshots).await {
+            Ok(replaced) => {
+                for blob_path in replaced {
+                    if let Err(e) = crud::host_clipboard::release_blob(&db_guard, &blob_path).await {
+                        error!("Failed to release blob {}: {}", blob_path, e);
+                    }
+                }
+            }
+            Err(e) => error!("Failed to add snapshots of {}: {}", entry.id, e),
+        }
+    }
+
+    // 按配置复制较小的文件, 截图本身已保存在 blob store 中
+    fn take_snapshots(content: &PasteboardContent) -> Vec<Snapshot> {
+        let snapshot_config = CONFIG.read().unwrap().user_config.snapshot_config.clone();
+        if !snapshot_config.enabled
+            || !(content.r#type == ContentType::File || content.r#type == ContentType::Image)
+        {
+            return Vec::new();
+        }
+        content
+            .paths()
+            .iter()
+            .filter(|path| !BLOB_STORE.contains(path))
+            .filter_map(|path| match snapshot::take(path, snapshot_config.max_size_bytes()) {
+                Ok(snapshot) => snapshot,
+                Err(e) => {
+                    error!("Failed to snapshot {}: {}", path, e);
+                    None
+                }
+            })
+            .collect()
     }
 }
 
 impl ClipboardHandler for ClipboardHandle {
     fn on_clipboard_change(&mut self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        let own_write = self.own_write_ids();
+        // 其他程序改写了剪贴板, 或 SuperCV 粘贴了队首以外的条目, 暂停记录时也要中断
+        if self.paste_queue.interrupt(own_write.as_deref()) {
+            debug!("paste queue interrupted by clipboard change");
+        }
+
+        if !self.capture.is_running() {
+            debug!("capture paused, clipboard change skipped");
+            return;
+        }
+
+        // SuperCV 粘贴时写入的内容已在历史中
+        if own_write.is_some() {
+            debug!("own clipboard write skipped");
+            // 剪贴板内容已变化, 之后再次复制上一条内容时仍需记录
+            self.last_hash.clear();
+            return;
+        }
+
+        // 在监听线程中立即获取来源, 避免焦点切换后取到错误的窗口
+        let source = source_app::current();
+        if CONFIG.read().unwrap().ignore_rules.ignores_app(source.as_ref()) {
+            debug!("ignored clip from {:?}", source.and_then(|s| s.app));
+            return;
+        }
+
+        // 密码管理器标记的内容不入库, 按配置只在内存中临时保留文本
+        if self.is_concealed() {
+            let concealed_config = CONFIG.read().unwrap().user_config.concealed_config.clone();
+            debug!(
+                "concealed clip from {:?}, keep: {}",
+                source.as_ref().and_then(|s| s.app.as_ref()),
+                concealed_config.keep
+            );
+            if concealed_config.keep {
+                if let Ok(text) = self.ctx.get_text() {
+                    CONCEALED_STASH.keep(text, source, concealed_config.timeout);
+                }
+            }
+            return;
+        }
+
         let mut content = None;
 
         match self.ctx.get_files() {
             Ok(file_urls) if !file_urls.is_empty() => {
-                for f_url in file_urls {
-                    content = self.new_file_content(f_url);
-                }
+                content = self.new_files_content(file_urls);
             }
             Ok(_) => {}
             Err(e) => {
@@ -100,21 +238,159 @@ impl ClipboardHandler for ClipboardHandle {
                 // 假设可以直接从 img 对象获取尺寸
                 content = self.new_img_content(&img);
             } else if let Ok(text) = self.ctx.get_text() {
-                content = self.new_text_content(text);
+                // 浏览器, IDE 中复制的内容通常同时带有 HTML/RTF
+                let html = self.ctx.get_html().ok().filter(|h| !h.trim().is_empty());
+                let rtf = self.ctx.get_rich_text().ok().filter(|r| !r.trim().is_empty());
+                content = if html.is_some() || rtf.is_some() {
+                    self.new_rich_text_content(text, html, rtf)
+                } else {
+                    self.new_text_content(text)
+                };
             }
         }
         // 将content push
-        if let Some(content) = content {
-            let _ = self.sender.send(content);
+        if let Some(mut content) = content {
+            // 日志中不能出现被忽略的内容本身
+            if let Some(rule) = CONFIG.read().unwrap().ignore_rules.matched_rule(&content) {
+                debug!("ignored {:?} clip: matched {}", content.r#type, rule);
+                return;
+            }
+            content.source = source;
+            // 大文本只保存压缩后的全文, 不再重复保存各个格式
+            let pending = (!content.is_large_text()).then(|| {
+                let has_image =
+                    content.r#type == ContentType::Image && BLOB_STORE.contains(&content.path);
+                PendingFormats {
+                    formats: self.representation_formats(has_image),
+                    generation: self.generation.load(Ordering::SeqCst),
+                }
+            });
+            let _ = self.sender.send((content, pending));
+        }
+    }
+}
+
+// 剪贴板协议本身使用的格式, 不属于复制的内容
+const META_FORMATS: [&str; 8] = [
+    "TARGETS",
+    "MULTIPLE",
+    "TIMESTAMP",
+    "SAVE_TARGETS",
+    "DELETE",
+    "INSERT_PROPERTY",
+    "INSERT_SELECTION",
+    "CLIPBOARD_MANAGER",
+];
+// 单个格式的大小上限, 超过时不保存该格式
+const MAX_REPRESENTATION_SIZE: usize = 32 * 1024 * 1024;
+// 一次复制读取的格式总大小上限, 达到后其余格式不再读取
+const MAX_REPRESENTATIONS_SIZE: usize = 64 * 1024 * 1024;
+
+impl ClipboardHandle {
+    // 本次复制需要保存的格式. 其他图片格式通常由来源应用从 PNG 即时转换, 有 PNG 时只保存 PNG;
+    // 位图已保存为图片 blob(has_image), PNG 格式入库时直接引用该 blob
+    fn representation_formats(&self, has_image: bool) -> Vec<String> {
+        let formats = match self.ctx.available_formats() {
+            Ok(formats) => formats,
+            Err(e) => {
+                error!("Error getting clipboard formats: {}", e);
+                return Vec::new();
+            }
+        };
+        let has_png = formats.iter().any(|format| format == FORMAT_PNG);
+        formats
+            .into_iter()
+            .filter(|format| !META_FORMATS.contains(&format.as_str()))
+            .filter(|format| {
+                !(format.starts_with("image/") && (has_image || (has_png && format != FORMAT_PNG)))
+            })
+            .collect()
+    }
+
+    // 在后台线程中读取格式内容, 限制总大小. 剪贴板内容只在这次复制仍然有效时才能读到,
+    // 读取前后剪贴板发生过变化时读到的可能是之后的内容, 全部丢弃
+    fn read_representations(
+        ctx: &ClipboardContext,
+        generation: &AtomicU64,
+        pending: PendingFormats,
+    ) -> Vec<Representation> {
+        let is_current = || generation.load(Ordering::SeqCst) == pending.generation;
+        if !is_current() {
+            debug!("clipboard changed before its formats were read");
+            return Vec::new();
+        }
+        let mut total = 0;
+        let representations = pending
+            .formats
+            .into_iter()
+            .filter_map(|format| {
+                if total >= MAX_REPRESENTATIONS_SIZE {
+                    debug!("skip format {}, total size limit reached", format);
+                    return None;
+                }
+                match ctx.get_buffer(&format) {
+                    Ok(data) if !data.is_empty() && data.len() <= MAX_REPRESENTATION_SIZE => {
+                        total += data.len();
+                        Some(Representation::new(format, data))
+                    }
+                    Ok(data) => {
+                        debug!("skip format {} ({} bytes)", format, data.len());
+                        None
+                    }
+                    Err(e) => {
+                        debug!("Error reading format {}: {}", format, e);
+                        None
+                    }
+                }
+            })
+            .collect();
+        if !is_current() {
+            debug!("clipboard changed while its formats were read");
+            return Vec::new();
+        }
+        representations
+    }
+
+    // SuperCV 自己写入时返回写入的条目 id
+    fn own_write_ids(&self) -> Option<Vec<i32>> {
+        let formats = match self.ctx.available_formats() {
+            Ok(formats) => formats,
+            Err(e) => {
+                error!("Error getting clipboard formats: {}", e);
+                return None;
+            }
+        };
+        if !own_write::is_own_write(&formats) {
+            return None;
+        }
+        let value = self
+            .ctx
+            .get_buffer(own_write::OWN_WRITE_FORMAT)
+            .unwrap_or_else(|e| {
+                debug!("Error reading own write marker: {}", e);
+                Vec::new()
+            });
+        Some(own_write::parse_marker(&value))
+    }
+
+    fn is_concealed(&self) -> bool {
+        match self.ctx.available_formats() {
+            Ok(formats) => {
+                concealed::is_concealed(&formats, |format| self.ctx.get_buffer(format).ok())
+            }
+            Err(e) => {
+                error!("Error getting clipboard formats: {}", e);
+                false
+            }
         }
     }
 }
 
+// 超过用户配置的上限时不记录
 pub(crate) fn string_is_large(input: &String) -> bool {
-    const LARGE_SIZE: usize = 250000;
     let input_len = input.len();
     debug!("get_sting_length: {}", input_len);
-    input_len > LARGE_SIZE
+    input_len > CONFIG.read().unwrap().user_config.text_config.max_size_bytes()
 }
 
 //
@@ -0,0 +1,198 @@
+use crate::core::source_app::SourceApp;
+use crate::utils::time::get_current_timestamp;
+use clipboard_rs::ClipboardContent;
+use lazy_static::lazy_static;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+lazy_static! {
+    pub static ref CONCEALED_STASH: ConcealedStash = ConcealedStash::default();
+}
+
+// 密码管理器复制密码时附带的标记格式, 出现即表示内容需要隐藏
+const CONCEALED_FORMATS: [&str; 3] = [
+    "application/x-nspasteboard-concealed-type",
+    "org.nspasteboard.ConcealedType",
+    "ExcludeClipboardContentFromMonitorProcessing",
+];
+// KDE/KeePassXC: 值为 secret 时表示隐藏
+const KDE_PASSWORD_HINT: &str = "x-kde-passwordManagerHint";
+// Windows: 值为 0 时不应进入剪贴板历史
+const CAN_INCLUDE_IN_HISTORY: &str = "CanIncludeInClipboardHistory";
+
+// formats 为剪贴板中提供的全部格式, read 用于读取标记格式的值
+pub fn is_concealed<F>(formats: &[String], read: F) -> bool
+where
+    F: Fn(&str) -> Option<Vec<u8>>,
+{
+    formats.iter().any(|format| match format.as_str() {
+        f if CONCEALED_FORMATS.contains(&f) => true,
+        KDE_PASSWORD_HINT => read(format)
+            .map(|value| String::from_utf8_lossy(&value).trim() == "secret")
+            .unwrap_or(false),
+        CAN_INCLUDE_IN_HISTORY => read(format)
+            .map(|value| value.iter().all(|&b| b == 0))
+            .unwrap_or(false),
+        _ => false,
+    })
+}
+
+// 粘贴隐藏内容时带上标记, 避免被自己重新记录到历史中
+pub fn concealed_markers() -> Vec<ClipboardContent> {
+    vec![
+        ClipboardContent::Other(KDE_PASSWORD_HINT.to_string(), b"secret".to_vec()),
+        ClipboardContent::Other(CONCEALED_FORMATS[1].to_string(), Vec::new()),
+        ClipboardContent::Other(CONCEALED_FORMATS[2].to_string(), Vec::new()),
+    ]
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ConcealedClip {
+    pub id: u64,
+    pub source_app: Option<String>,
+    pub timestamp: i64,
+    pub expires_at: i64,
+    #[serde(skip)]
+    pub content: String, // 不返回给前端, 只能通过粘贴使用
+}
+
+// 隐藏内容只保存在内存中, 到期时由后台任务清除, 读写前也会先清除
+#[derive(Debug, Default)]
+pub struct ConcealedStash {
+    clips: Mutex<Vec<ConcealedClip>>,
+    next_id: AtomicU64,
+    kept: Notify, // 保存新内容时唤醒后台任务重新计算到期时间
+}
+
+impl ConcealedStash {
+    pub fn keep(&self, content: String, source: Option<SourceApp>, timeout: u64) {
+        self.purge();
+        let now = get_current_timestamp();
+        let clip = ConcealedClip {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            source_app: source.and_then(|source| source.app),
+            timestamp: now,
+            // timeout 来自配置文件, 过大时视为不过期
+            expires_at: now.saturating_add(i64::try_from(timeout).unwrap_or(i64::MAX)),
+            content,
+        };
+        let mut clips = self.clips.lock().unwrap();
+        // 同一内容只保留最近一次
+        clips.retain(|c| c.content != clip.content);
+        clips.push(clip);
+        self.kept.notify_one();
+    }
+
+    // 按复制时间倒序返回未过期的内容
+    pub fn list(&self) -> Vec<ConcealedClip> {
+        self.purge();
+        let clips = self.clips.lock().unwrap();
+        clips.iter().rev().cloned().collect()
+    }
+
+    pub fn get_content(&self, id: u64) -> Option<String> {
+        self.purge();
+        let clips = self.clips.lock().unwrap();
+        clips.iter().find(|c| c.id == id).map(|c| c.content.clone())
+    }
+
+    pub fn clear(&self) {
+        self.clips.lock().unwrap().clear();
+    }
+
+    // 睡眠到最早的内容到期, 没有人读取时内容也不会留在内存中; 需要在 tokio 运行时中调用
+    pub fn spawn_expiry_worker(&'static self) {
+        tokio::spawn(async move {
+            loop {
+                match self.purge() {
+                    Some(expires_at) => {
+                        let secs = (expires_at - get_current_timestamp()).max(0) as u64;
+                        tokio::select! {
+                            _ = tokio::time::sleep(Duration::from_secs(secs)) => {}
+                            _ = self.kept.notified() => {}
+                        }
+                    }
+                    None => self.kept.notified().await,
+                }
+            }
+        });
+    }
+
+    // 清除过期内容, 返回剩余内容中最早的到期时间
+    fn purge(&self) -> Option<i64> {
+        let now = get_current_timestamp();
+        let mut clips = self.clips.lock().unwrap();
+        clips.retain(|c| c.expires_at > now);
+        clips.iter().map(|c| c.expires_at).min()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn formats(formats: &[&str]) -> Vec<String> {
+        formats.iter().map(|f| f.to_string()).collect()
+    }
+
+    #[test]
+    fn test_is_concealed() {
+        let read = |value: &'static [u8]| move |_: &str| Some(value.to_vec());
+
+        let plain = formats(&["UTF8_STRING", "text/plain"]);
+        assert!(!is_concealed(&plain, read(b"")));
+
+        let mac = formats(&["public.utf8-plain-text", "org.nspasteboard.ConcealedType"]);
+        assert!(is_concealed(&mac, read(b"")));
+
+        let kde = formats(&["text/plain", "x-kde-passwordManagerHint"]);
+        assert!(is_concealed(&kde, read(b"secret\n")));
+        assert!(!is_concealed(&kde, read(b"public")));
+        assert!(!is_concealed(&kde, |_| None));
+
+        let windows = formats(&["CF_UNICODETEXT", "CanIncludeInClipboardHistory"]);
+        assert!(is_concealed(&windows, read(&[0, 0, 0, 0])));
+        assert!(!is_concealed(&windows, read(&[1, 0, 0, 0])));
+    }
+
+    #[test]
+    fn test_stash_expires() {
+        lazy_static! {
+            static ref STASH: ConcealedStash = ConcealedStash::default();
+        }
+        STASH.keep("hunter2".to_string(), None, 60);
+        STASH.keep("hunter2".to_string(), None, 60);
+        STASH.keep("expired".to_string(), None, 0);
+        STASH.keep("forever".to_string(), None, u64::MAX);
+
+        let clips = STASH.list();
+        assert_eq!(clips.len(), 2);
+        assert_eq!(clips[0].expires_at, i64::MAX);
+        assert_eq!(STASH.get_content(clips[1].id), Some("hunter2".to_string()));
+        assert!(!serde_json::to_string(&clips[1])
+            .unwrap()
+            .contains("hunter2"));
+
+        STASH.clear();
+        assert!(STASH.list().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_expiry_worker() {
+        lazy_static! {
+            static ref STASH: ConcealedStash = ConcealedStash::default();
+        }
+        STASH.spawn_expiry_worker();
+        STASH.keep("kept".to_string(), None, 60);
+        STASH.keep("expired".to_string(), None, 0);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        // 不经过 list/get_content, 直接检查内存中的内容
+        let clips = STASH.clips.lock().unwrap();
+        assert_eq!(clips.len(), 1);
+        assert_eq!(clips[0].content, "kept");
+    }
+}
@@ -0,0 +1,150 @@
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// 第一层最多列出的条目数
+const MAX_LISTED: usize = 200;
+// 最多遍历的条目数, 超过时统计不完整
+const MAX_WALKED: usize = 200_000;
+
+// 目录的递归统计以及第一层的列表, 以 JSON 保存在 summary 列
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DirSummary {
+    pub file_count: u64,
+    pub dir_count: u64,
+    pub total_size: u64,
+    pub entries: Vec<DirItem>, // 目录在前, 按名称排序
+    pub truncated: bool,       // 条目过多, 统计和列表不完整
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DirItem {
+    pub name: String,
+    pub is_dir: bool,
+    pub size: u64, // 子目录为递归大小
+}
+
+#[derive(Default)]
+struct Totals {
+    files: u64,
+    dirs: u64,
+    size: u64,
+}
+
+pub fn summarize<P: AsRef<Path>>(path: P) -> io::Result<DirSummary> {
+    let mut budget = MAX_WALKED;
+    let mut summary = DirSummary::default();
+    for entry in fs::read_dir(path)?.flatten() {
+        if budget == 0 {
+            break;
+        }
+        budget -= 1;
+        // 不跟随符号链接, 避免重复统计和循环
+        let Ok(metadata) = entry.path().symlink_metadata() else {
+            continue;
+        };
+        let item = if metadata.is_dir() {
+            let totals = walk(&entry.path(), &mut budget);
+            summary.file_count += totals.files;
+            summary.dir_count += totals.dirs + 1;
+            summary.total_size += totals.size;
+            DirItem {
+                name: entry.file_name().to_string_lossy().to_string(),
+                is_dir: true,
+                size: totals.size,
+            }
+        } else {
+            summary.file_count += 1;
+            summary.total_size += metadata.len();
+            DirItem {
+                name: entry.file_name().to_string_lossy().to_string(),
+                is_dir: false,
+                size: metadata.len(),
+            }
+        };
+        summary.entries.push(item);
+    }
+    if budget == 0 {
+        summary.truncated = true;
+    }
+
+    summary
+        .entries
+        .sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+    if summary.entries.len() > MAX_LISTED {
+        summary.entries.truncate(MAX_LISTED);
+        summary.truncated = true;
+    }
+    Ok(summary)
+}
+
+// 无权限读取的子目录跳过
+fn walk(dir: &Path, budget: &mut usize) -> Totals {
+    let mut totals = Totals::default();
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            debug!("skip {}: {}", dir.display(), e);
+            return totals;
+        }
+    };
+    for entry in entries.flatten() {
+        if *budget == 0 {
+            break;
+        }
+        *budget -= 1;
+        let Ok(metadata) = entry.path().symlink_metadata() else {
+            continue;
+        };
+        if metadata.is_dir() {
+            let sub = walk(&entry.path(), budget);
+            totals.files += sub.files;
+            totals.dirs += sub.dirs + 1;
+            totals.size += sub.size;
+        } else {
+            totals.files += 1;
+            totals.size += metadata.len();
+        }
+    }
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summarize() {
+        let root = std::env::temp_dir().join(format!("super-cv-dir-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("src/nested")).unwrap();
+        fs::write(root.join("README.md"), "12345").unwrap();
+        fs::write(root.join("src/main.rs"), "123").unwrap();
+        fs::write(root.join("src/nested/a.rs"), "1234567").unwrap();
+
+        let summary = summarize(&root).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(summary.file_count, 3);
+        assert_eq!(summary.dir_count, 2);
+        assert_eq!(summary.total_size, 15);
+        assert!(!summary.truncated);
+        assert_eq!(
+            summary.entries,
+            vec![
+                DirItem {
+                    name: "src".to_string(),
+                    is_dir: true,
+                    size: 10,
+                },
+                DirItem {
+                    name: "README.md".to_string(),
+                    is_dir: false,
+                    size: 5,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,165 @@
+use crate::core::blob_store::BLOB_STORE;
+use crate::core::pasteboard::{ContentType, PasteboardContent};
+use crate::core::source_app::SourceApp;
+use crate::utils::config::IgnoreConfig;
+use crate::utils::file::expand_home;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use regex::Regex;
+
+// 由 IgnoreConfig 编译得到的忽略规则, 命中的剪贴板内容不会入库
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreRules {
+    apps: Vec<String>,    // 小写的应用名, 与 WM_CLASS 的实例名或类名比较
+    patterns: Vec<Regex>, // 匹配文本内容
+    globs: GlobSet,       // 匹配文件路径
+    glob_sources: Vec<String>,
+}
+
+impl IgnoreRules {
+    // 无效的规则会被跳过, 并在第二个返回值中说明原因
+    pub fn new(config: &IgnoreConfig) -> (Self, Vec<String>) {
+        let mut errors = Vec::new();
+
+        let apps = config
+            .apps
+            .iter()
+            .map(|app| app.trim().to_lowercase())
+            .filter(|app| !app.is_empty())
+            .collect();
+
+        let patterns = config
+            .patterns
+            .iter()
+            .filter_map(|pattern| match Regex::new(pattern) {
+                Ok(regex) => Some(regex),
+                Err(e) => {
+                    errors.push(format!("invalid pattern {:?}: {}", pattern, e));
+                    None
+                }
+            })
+            .collect();
+
+        let mut builder = GlobSetBuilder::new();
+        let mut glob_sources = Vec::new();
+        for path in config.paths.iter() {
+            match Glob::new(&expand_home(path.trim())) {
+                Ok(glob) => {
+                    builder.add(glob);
+                    glob_sources.push(path.clone());
+                }
+                Err(e) => errors.push(format!("invalid path glob {:?}: {}", path, e)),
+            }
+        }
+        let globs = builder.build().unwrap_or_else(|e| {
+            errors.push(format!("invalid path globs: {}", e));
+            glob_sources.clear();
+            GlobSet::empty()
+        });
+
+        let rules = IgnoreRules {
+            apps,
+            patterns,
+            globs,
+            glob_sources,
+        };
+        (rules, errors)
+    }
+
+    // 来源应用是否被忽略, 在读取剪贴板内容之前调用
+    // WM_CLASS 的实例名和类名任一匹配即可, 不区分大小写
+    pub fn ignores_app(&self, source: Option<&SourceApp>) -> bool {
+        source
+            .map(|source| {
+                [&source.app, &source.instance]
+                    .into_iter()
+                    .flatten()
+                    .any(|name| self.apps.contains(&name.to_lowercase()))
+            })
+            .unwrap_or(false)
+    }
+
+    // 返回命中的规则描述, 不包含剪贴板内容本身
+    pub fn matched_rule(&self, content: &PasteboardContent) -> Option<String> {
+        match content.r#type {
+            ContentType::Text | ContentType::RichText => self
+                .patterns
+                .iter()
+                .position(|regex| regex.is_match(&content.text_content))
+                .map(|i| format!("pattern #{}", i + 1)),
+            // 直接复制的图片保存在 blob store 中, 路径与用户文件无关
+            ContentType::File | ContentType::Image | ContentType::Directory => content
+                .paths()
+                .iter()
+                .filter(|path| !BLOB_STORE.contains(path))
+                .find_map(|path| self.globs.matches(path).first().copied())
+                .map(|i| format!("path glob {:?}", self.glob_sources[i])),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules() -> IgnoreRules {
+        let (rules, errors) = IgnoreRules::new(&IgnoreConfig {
+            apps: vec!["KeePassXC".to_string(), " ".to_string()],
+            patterns: vec![r"^ghp_[A-Za-z0-9]{36}$".to_string(), "(".to_string()],
+            paths: vec!["/secrets/**".to_string(), "**/*.kdbx".to_string()],
+        });
+        assert_eq!(errors.len(), 1);
+        rules
+    }
+
+    fn source(app: &str, instance: &str) -> SourceApp {
+        SourceApp {
+            app: Some(app.to_string()),
+            title: None,
+            instance: Some(instance.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_ignores_app() {
+        let rules = rules();
+        assert!(rules.ignores_app(Some(&source("KeePassXC", "keepassxc"))));
+        assert!(rules.ignores_app(Some(&source("Keepassxc-app", "KeePassXC"))));
+        assert!(rules.ignores_app(Some(&source("keepassxc", "keepassxc-bin"))));
+        assert!(!rules.ignores_app(Some(&source("Gnome-terminal", "gnome-terminal"))));
+        assert!(!rules.ignores_app(Some(&SourceApp::default())));
+        assert!(!rules.ignores_app(None));
+    }
+
+    #[test]
+    fn test_matched_rule() {
+        let rules = rules();
+        let text = |s: &str| {
+            PasteboardContent::new(s.to_string(), ContentType::Text, String::new(), None, 0)
+        };
+        let files = |p: &str| {
+            PasteboardContent::new(
+                String::new(),
+                ContentType::File,
+                String::new(),
+                Some(p.to_string()),
+                0,
+            )
+        };
+
+        let token = format!("ghp_{}", "a".repeat(36));
+        assert_eq!(
+            rules.matched_rule(&text(&token)),
+            Some("pattern #1".to_string())
+        );
+        assert_eq!(rules.matched_rule(&text("hello")), None);
+        assert_eq!(
+            rules.matched_rule(&files("/home/a.txt\n/home/db.kdbx")),
+            Some("path glob \"**/*.kdbx\"".to_string())
+        );
+        assert_eq!(
+            rules.matched_rule(&files("/secrets/a/b.txt")),
+            Some("path glob \"/secrets/**\"".to_string())
+        );
+        assert_eq!(rules.matched_rule(&files("/home/a.txt")), None);
+    }
+}
@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+
+// 多条文本粘贴时的连接方式
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JoinOptions {
+    pub separator: Separator,
+    pub numbering: bool,      // 每条前加 "1. " 形式的序号
+    pub quote: Option<Quote>, // 每条加上引号
+}
+
+// 前端传入 {"kind": "custom", "value": " | "} 形式
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "kind", content = "value")]
+pub enum Separator {
+    #[default]
+    Newline,
+    Comma, // ", "
+    Tab,
+    Custom(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Quote {
+    Double, // 按 JSON 字符串转义
+    Single, // 按 SQL 字符串转义, ' 写作 ''
+}
+
+impl Separator {
+    fn as_str(&self) -> &str {
+        match self {
+            Separator::Newline => "\n",
+            Separator::Comma => ", ",
+            Separator::Tab => "\t",
+            Separator::Custom(separator) => separator,
+        }
+    }
+}
+
+// 按 texts 的顺序连接
+pub fn join<S: AsRef<str>>(texts: &[S], options: &JoinOptions) -> String {
+    texts
+        .iter()
+        .enumerate()
+        .map(|(i, text)| {
+            let text = match options.quote {
+                Some(quote) => quote_text(text.as_ref(), quote),
+                None => text.as_ref().to_string(),
+            };
+            if options.numbering {
+                format!("{}. {}", i + 1, text)
+            } else {
+                text
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(options.separator.as_str())
+}
+
+fn quote_text(text: &str, quote: Quote) -> String {
+    match quote {
+        Quote::Double => serde_json::to_string(text).unwrap(),
+        Quote::Single => format!("'{}'", text.replace('\'', "''")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_join() {
+        let texts = ["a", "it's", "say \"hi\""];
+        assert_eq!(join(&texts, &JoinOptions::default()), "a\nit's\nsay \"hi\"");
+
+        let options = JoinOptions {
+            separator: Separator::Comma,
+            quote: Some(Quote::Single),
+            ..Default::default()
+        };
+        assert_eq!(join(&texts, &options), r#"'a', 'it''s', 'say "hi"'"#);
+
+        let options = JoinOptions {
+            separator: Separator::Custom(" | ".to_string()),
+            numbering: true,
+            quote: Some(Quote::Double),
+        };
+        assert_eq!(
+            join(&texts, &options),
+            r#"1. "a" | 2. "it's" | 3. "say \"hi\"""#
+        );
+    }
+
+    #[test]
+    fn test_deserialize_options() {
+        let options: JoinOptions = serde_json::from_str(
+            r#"{"separator": {"kind": "custom", "value": ";"}, "quote": "double"}"#,
+        )
+        .unwrap();
+        assert_eq!(options.separator, Separator::Custom(";".to_string()));
+        assert!(!options.numbering);
+        assert_eq!(options.quote, Some(Quote::Double));
+
+        let options: JoinOptions =
+            serde_json::from_str(r#"{"separator": {"kind": "tab"}}"#).unwrap();
+        assert_eq!(join(&["a", "b"], &options), "a\tb");
+    }
+}
@@ -0,0 +1,66 @@
+use crate::core::blob_store::{BlobPin, BLOB_STORE};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// 超过该大小的文本压缩后保存到 blob store
+pub const LARGE_TEXT_SIZE: usize = 250000;
+// 记录中保留的预览大小, 用于展示和搜索
+pub const PREVIEW_SIZE: usize = 32 * 1024;
+pub const LARGE_TEXT_BLOB_EXT: &str = "txt.zst";
+
+const ZSTD_LEVEL: i32 = 3;
+
+// 在字符边界处截断到 PREVIEW_SIZE 以内
+pub fn preview(text: &str) -> &str {
+    if text.len() <= PREVIEW_SIZE {
+        return text;
+    }
+    let mut end = PREVIEW_SIZE;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+pub fn compress(text: &str) -> io::Result<Vec<u8>> {
+    zstd::encode_all(text.as_bytes(), ZSTD_LEVEL)
+}
+
+pub fn decompress(data: &[u8]) -> io::Result<String> {
+    let bytes = zstd::decode_all(data)?;
+    String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+// 以全文 hash 为键保存, 相同文本只保存一份
+pub fn store(hash: &str, text: &str) -> io::Result<BlobPin> {
+    BLOB_STORE.put_pinned(hash, LARGE_TEXT_BLOB_EXT, &compress(text)?)
+}
+
+pub fn load<P: AsRef<Path>>(path: P) -> io::Result<String> {
+    decompress(&fs::read(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview() {
+        assert_eq!(preview("short"), "short");
+
+        let text = "中".repeat(PREVIEW_SIZE);
+        let p = preview(&text);
+        assert!(p.len() <= PREVIEW_SIZE);
+        assert!(p.len() > PREVIEW_SIZE - 3);
+        assert!(text.starts_with(p));
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let text = "log line 中文\n".repeat(50000);
+        let compressed = compress(&text).unwrap();
+        assert!(compressed.len() < text.len() / 10);
+        assert_eq!(decompress(&compressed).unwrap(), text);
+    }
+}
@@ -1,2 +1,18 @@
 pub mod pasteboard;
 pub mod clipboard;
+pub mod blob_store;
+pub mod retention;
+pub mod query;
+pub mod source_app;
+pub mod ignore;
+pub mod concealed;
+pub mod capture;
+pub mod large_text;
+pub mod thumbnail;
+pub mod snapshot;
+pub mod dir_summary;
+pub mod own_write;
+pub mod transform;
+pub mod join;
+pub mod paste_queue;
+pub mod paste_watch;
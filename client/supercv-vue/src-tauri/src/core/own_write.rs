@@ -0,0 +1,40 @@
+use clipboard_rs::ClipboardContent;
+
+// SuperCV 写入剪贴板时附带的私有格式, 值为条目 id; 监听到该格式时不再记录
+pub const OWN_WRITE_FORMAT: &str = "application/x-supercv-entry";
+
+pub fn marker(ids: &[i32]) -> ClipboardContent {
+    ClipboardContent::Other(OWN_WRITE_FORMAT.to_string(), marker_value(ids))
+}
+
+// 以逗号分隔的条目 id
+pub fn marker_value(ids: &[i32]) -> Vec<u8> {
+    ids.iter()
+        .map(|id| id.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+        .into_bytes()
+}
+
+pub fn parse_marker(value: &[u8]) -> Vec<i32> {
+    String::from_utf8_lossy(value)
+        .split(',')
+        .filter_map(|id| id.trim().parse().ok())
+        .collect()
+}
+
+pub fn is_own_write(formats: &[String]) -> bool {
+    formats.iter().any(|format| format == OWN_WRITE_FORMAT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_marker_value() {
+        assert_eq!(parse_marker(&marker_value(&[3, 12])), vec![3, 12]);
+        assert_eq!(parse_marker(&marker_value(&[])), Vec::<i32>::new());
+        assert_eq!(parse_marker(b"7,x"), vec![7]);
+    }
+}
@@ -0,0 +1,174 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Mutex, MutexGuard};
+use tokio::sync::Notify;
+
+// 依次粘贴的队列, 队首条目放在系统剪贴板中.
+// X11 上文本条目由 paste_watch 自己提供内容, 焦点窗口读取后自动推进; macOS, Windows
+// 和 Wayland 不会通知剪贴板内容何时被读取, 文件和图片条目也不由 paste_watch 提供,
+// 这些情况由"粘贴下一条"全局快捷键或前端调用 advance 推进.
+// 剪贴板被改写为其他内容时队首已不在剪贴板中, 队列随之中断
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum QueueMode {
+    #[default]
+    Fifo, // 按给出的顺序
+    Lifo, // 最后给出的先粘贴
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct QueueState {
+    pub mode: QueueMode,
+    pub current: Option<i32>, // 当前在剪贴板中的条目
+    pub pending: Vec<i32>,    // 之后依次粘贴的条目
+    pub total: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct PasteQueue {
+    mode: QueueMode,
+    items: VecDeque<i32>, // 队首为当前条目
+    total: usize,
+}
+
+impl PasteQueue {
+    // 替换整个队列, 返回新的当前条目
+    pub fn build(&mut self, ids: Vec<i32>, mode: QueueMode) -> Option<i32> {
+        self.mode = mode;
+        self.total = ids.len();
+        self.items = match mode {
+            QueueMode::Fifo => ids.into_iter().collect(),
+            QueueMode::Lifo => ids.into_iter().rev().collect(),
+        };
+        self.current()
+    }
+
+    pub fn current(&self) -> Option<i32> {
+        self.items.front().copied()
+    }
+
+    // 当前条目已粘贴, 返回下一条
+    pub fn advance(&mut self) -> Option<i32> {
+        self.items.pop_front();
+        self.current()
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+        self.total = 0;
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.items.is_empty()
+    }
+
+    pub fn state(&self) -> QueueState {
+        QueueState {
+            mode: self.mode,
+            current: self.current(),
+            pending: self.items.iter().skip(1).copied().collect(),
+            total: self.total,
+        }
+    }
+}
+
+// 命令和剪贴板监听共享的队列, 队列中断或队首被粘贴后通知界面刷新
+#[derive(Debug, Default)]
+pub struct SharedPasteQueue {
+    queue: Mutex<PasteQueue>,
+    interrupted: Notify,
+    pasted: Notify,
+}
+
+impl SharedPasteQueue {
+    pub fn lock(&self) -> MutexGuard<'_, PasteQueue> {
+        self.queue.lock().unwrap()
+    }
+
+    // 剪贴板变化时调用, written 为 SuperCV 写入的条目 id; 写入的不是队首时清空队列,
+    // 返回队列是否因此中断
+    pub fn interrupt(&self, written: Option<&[i32]>) -> bool {
+        let mut queue = self.lock();
+        let Some(current) = queue.current() else {
+            return false;
+        };
+        if written == Some(&[current][..]) {
+            return false;
+        }
+        queue.clear();
+        self.interrupted.notify_one();
+        true
+    }
+
+    // 等待下一次中断
+    pub async fn interrupted(&self) {
+        self.interrupted.notified().await
+    }
+
+    // 条目 id 被粘贴, 仍是队首时通知推进
+    pub fn notify_pasted(&self, id: i32) {
+        if self.lock().current() == Some(id) {
+            self.pasted.notify_one();
+        }
+    }
+
+    // 等待队首被粘贴
+    pub async fn pasted(&self) {
+        self.pasted.notified().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fifo() {
+        let mut queue = PasteQueue::default();
+        assert_eq!(queue.build(vec![1, 2, 3], QueueMode::Fifo), Some(1));
+        assert_eq!(queue.state().pending, vec![2, 3]);
+        assert_eq!(queue.advance(), Some(2));
+        assert_eq!(queue.advance(), Some(3));
+        assert_eq!(queue.advance(), None);
+        assert!(!queue.is_active());
+        assert_eq!(queue.advance(), None);
+        assert_eq!(queue.state().total, 3);
+    }
+
+    #[test]
+    fn test_lifo() {
+        let mut queue = PasteQueue::default();
+        assert_eq!(queue.build(vec![1, 2, 3], QueueMode::Lifo), Some(3));
+        assert_eq!(queue.advance(), Some(2));
+        assert_eq!(queue.advance(), Some(1));
+
+        queue.clear();
+        assert_eq!(
+            queue.state(),
+            QueueState {
+                mode: QueueMode::Lifo,
+                current: None,
+                pending: vec![],
+                total: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_interrupt() {
+        let shared = SharedPasteQueue::default();
+        assert!(!shared.interrupt(None));
+
+        shared.lock().build(vec![1, 2], QueueMode::Fifo);
+        // 写入的是队首时队列继续
+        assert!(!shared.interrupt(Some(&[1])));
+        assert!(shared.lock().is_active());
+        // 粘贴了其他条目
+        assert!(shared.interrupt(Some(&[2])));
+        assert!(!shared.lock().is_active());
+
+        shared.lock().build(vec![1, 2], QueueMode::Fifo);
+        assert!(shared.interrupt(None));
+        assert!(!shared.interrupt(None));
+    }
+}
@@ -0,0 +1,302 @@
+// 检测粘贴队列的队首何时被粘贴.
+// 只有 X11 能做到: SelectionRequest 只发给 CLIPBOARD 的所有者, 因此由 SuperCV 自己持有
+// CLIPBOARD 并提供队首的各个格式, 焦点窗口读取内容后视为已粘贴.
+// 其他平台以及没有 X11 的会话返回 false, 由调用方改为普通写入剪贴板
+use crate::core::pasteboard::FORMAT_TEXT;
+use std::time::Duration;
+
+// 一次粘贴通常会连续请求多个格式, 最后一次请求之后等待这么久再通知
+const SETTLE_TIME: Duration = Duration::from_millis(300);
+
+// X11 应用通常请求 UTF8_STRING, 保存的格式中没有时由 text/plain 的内容提供
+const TEXT_ALIASES: [&str; 3] = ["UTF8_STRING", "text/plain;charset=utf-8", "TEXT"];
+
+// targets 为格式名和内容, 持有 CLIPBOARD 直到其他程序或下一次 serve 接管;
+// 每次被焦点窗口读取后调用 on_pasted
+#[cfg(target_os = "linux")]
+pub fn serve<F>(targets: Vec<(String, Vec<u8>)>, on_pasted: F) -> bool
+where
+    F: Fn() + Send + 'static,
+{
+    match x11::serve(with_text_aliases(targets), Box::new(on_pasted)) {
+        Ok(()) => true,
+        Err(e) => {
+            log::debug!("Failed to serve clipboard: {}", e);
+            false
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn serve<F>(_targets: Vec<(String, Vec<u8>)>, _on_pasted: F) -> bool
+where
+    F: Fn() + Send + 'static,
+{
+    false
+}
+
+fn with_text_aliases(mut targets: Vec<(String, Vec<u8>)>) -> Vec<(String, Vec<u8>)> {
+    let text = targets
+        .iter()
+        .find(|(format, _)| format == FORMAT_TEXT)
+        .map(|(_, data)| data.clone());
+    if let Some(text) = text {
+        for alias in TEXT_ALIASES {
+            if !targets.iter().any(|(format, _)| format == alias) {
+                targets.push((alias.to_string(), text.clone()));
+            }
+        }
+    }
+    targets
+}
+
+#[cfg(target_os = "linux")]
+mod x11 {
+    use super::SETTLE_TIME;
+    use crate::core::own_write::OWN_WRITE_FORMAT;
+    use lazy_static::lazy_static;
+    use std::error::Error;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+    use x11rb::connection::{Connection, RequestConnection};
+    use x11rb::protocol::xproto::{
+        Atom, AtomEnum, ConnectionExt, CreateWindowAux, EventMask, PropMode, SelectionNotifyEvent,
+        SelectionRequestEvent, Window, WindowClass, SELECTION_NOTIFY_EVENT,
+    };
+    use x11rb::protocol::Event;
+    use x11rb::rust_connection::RustConnection;
+    use x11rb::wrapper::ConnectionExt as _;
+    use x11rb::{COPY_DEPTH_FROM_PARENT, COPY_FROM_PARENT, CURRENT_TIME, NONE};
+
+    const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+    lazy_static! {
+        // 正在提供内容的线程, 新的队首接管 CLIPBOARD 后通知旧线程退出
+        static ref RUNNING: Mutex<Option<Arc<AtomicBool>>> = Mutex::new(None);
+    }
+
+    struct Server {
+        conn: RustConnection,
+        root: Window,
+        clipboard: Atom,
+        targets_atom: Atom,
+        marker_atom: Atom,
+        active_window_atom: Atom,
+        targets: Vec<(Atom, Vec<u8>)>,
+    }
+
+    pub(super) fn serve(
+        targets: Vec<(String, Vec<u8>)>,
+        on_pasted: Box<dyn Fn() + Send>,
+    ) -> Result<(), Box<dyn Error>> {
+        let (conn, screen_num) = x11rb::connect(None)?;
+        let root = conn.setup().roots[screen_num].root;
+
+        // 超过单个请求大小的格式需要 INCR 分段传输, 不提供
+        let max_size = conn.maximum_request_bytes().saturating_sub(64);
+        let mut atoms = Vec::new();
+        for (format, data) in targets {
+            if data.len() <= max_size {
+                atoms.push((intern_atom(&conn, &format)?, data));
+            }
+        }
+        if atoms.is_empty() {
+            return Err("no format fits in a single request".into());
+        }
+
+        let window = conn.generate_id()?;
+        conn.create_window(
+            COPY_DEPTH_FROM_PARENT,
+            window,
+            root,
+            0,
+            0,
+            1,
+            1,
+            0,
+            WindowClass::INPUT_OUTPUT,
+            COPY_FROM_PARENT,
+            &CreateWindowAux::new(),
+        )?;
+        let clipboard = intern_atom(&conn, "CLIPBOARD")?;
+        conn.set_selection_owner(window, clipboard, CURRENT_TIME)?;
+        if conn.get_selection_owner(clipboard)?.reply()?.owner != window {
+            return Err("failed to own CLIPBOARD".into());
+        }
+
+        let targets_atom = intern_atom(&conn, "TARGETS")?;
+        let marker_atom = intern_atom(&conn, OWN_WRITE_FORMAT)?;
+        let active_window_atom = intern_atom(&conn, "_NET_ACTIVE_WINDOW")?;
+        let server = Server {
+            conn,
+            root,
+            clipboard,
+            targets_atom,
+            marker_atom,
+            active_window_atom,
+            targets: atoms,
+        };
+
+        let stop = Arc::new(AtomicBool::new(false));
+        if let Some(previous) = RUNNING.lock().unwrap().replace(stop.clone()) {
+            previous.store(true, Ordering::SeqCst);
+        }
+        std::thread::spawn(move || {
+            // 连接关闭时 X server 释放窗口和 CLIPBOARD
+            if let Err(e) = server.run(&stop, on_pasted) {
+                log::error!("Clipboard server stopped: {}", e);
+            }
+        });
+        Ok(())
+    }
+
+    impl Server {
+        fn run(
+            &self,
+            stop: &AtomicBool,
+            on_pasted: Box<dyn Fn() + Send>,
+        ) -> Result<(), Box<dyn Error>> {
+            let mut pasted_at: Option<Instant> = None;
+            while !stop.load(Ordering::SeqCst) {
+                match self.conn.poll_for_event()? {
+                    Some(Event::SelectionRequest(request)) => {
+                        // 判断失败时不推进, 仍可用快捷键推进
+                        if self.reply(&request)?
+                            && self.is_focused(request.requestor).unwrap_or(false)
+                        {
+                            pasted_at = Some(Instant::now());
+                        }
+                    }
+                    // 其他程序或下一个队首接管了 CLIPBOARD
+                    Some(Event::SelectionClear(_)) => break,
+                    Some(_) => {}
+                    None => {
+                        if pasted_at.is_some_and(|at| at.elapsed() >= SETTLE_TIME) {
+                            pasted_at = None;
+                            on_pasted();
+                        }
+                        std::thread::sleep(POLL_INTERVAL);
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        // 回复一次请求, 返回是否提供了内容本身; TARGETS 和 own_write 标记不算
+        fn reply(&self, request: &SelectionRequestEvent) -> Result<bool, Box<dyn Error>> {
+            // 旧的客户端不指定 property, 此时写入 target
+            let property = if request.property == NONE {
+                request.target
+            } else {
+                request.property
+            };
+            let data = self
+                .targets
+                .iter()
+                .find(|(atom, _)| *atom == request.target)
+                .map(|(_, data)| data);
+            let (property, served) = if request.selection != self.clipboard {
+                (NONE, false)
+            } else if request.target == self.targets_atom {
+                let mut atoms = vec![self.targets_atom];
+                atoms.extend(self.targets.iter().map(|(atom, _)| *atom));
+                self.conn.change_property32(
+                    PropMode::REPLACE,
+                    request.requestor,
+                    property,
+                    AtomEnum::ATOM,
+                    &atoms,
+                )?;
+                (property, false)
+            } else if let Some(data) = data {
+                self.conn.change_property8(
+                    PropMode::REPLACE,
+                    request.requestor,
+                    property,
+                    request.target,
+                    data,
+                )?;
+                (property, request.target != self.marker_atom)
+            } else {
+                (NONE, false)
+            };
+
+            let event = SelectionNotifyEvent {
+                response_type: SELECTION_NOTIFY_EVENT,
+                sequence: 0,
+                time: request.time,
+                requestor: request.requestor,
+                selection: request.selection,
+                target: request.target,
+                property,
+            };
+            self.conn
+                .send_event(false, request.requestor, EventMask::NO_EVENT, event)?;
+            self.conn.flush()?;
+            Ok(served)
+        }
+
+        // 剪贴板管理器等后台程序也会读取内容, 只有焦点窗口的读取才算粘贴
+        fn is_focused(&self, mut window: Window) -> Result<bool, Box<dyn Error>> {
+            let active = self
+                .conn
+                .get_property(
+                    false,
+                    self.root,
+                    self.active_window_atom,
+                    AtomEnum::WINDOW,
+                    0,
+                    1,
+                )?
+                .reply()?
+                .value32()
+                .and_then(|mut value| value.next())
+                .unwrap_or(NONE);
+            // 窗口管理器不支持 _NET_ACTIVE_WINDOW 时无法区分, 都算作粘贴
+            if active == NONE {
+                return Ok(true);
+            }
+            while window != NONE && window != self.root {
+                if window == active {
+                    return Ok(true);
+                }
+                window = self.conn.query_tree(window)?.reply()?.parent;
+            }
+            Ok(false)
+        }
+    }
+
+    fn intern_atom(conn: &RustConnection, name: &str) -> Result<Atom, Box<dyn Error>> {
+        Ok(conn.intern_atom(false, name.as_bytes())?.reply()?.atom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_text_aliases() {
+        let targets = with_text_aliases(vec![
+            ("text/plain".to_string(), b"hi".to_vec()),
+            ("UTF8_STRING".to_string(), b"utf8".to_vec()),
+            ("text/html".to_string(), b"<b>hi</b>".to_vec()),
+        ]);
+        let formats: Vec<&str> = targets.iter().map(|(format, _)| format.as_str()).collect();
+        assert_eq!(
+            formats,
+            vec![
+                "text/plain",
+                "UTF8_STRING",
+                "text/html",
+                "text/plain;charset=utf-8",
+                "TEXT"
+            ]
+        );
+        assert_eq!(targets[3].1, b"hi");
+
+        let html = vec![("text/html".to_string(), b"<b>hi</b>".to_vec())];
+        assert_eq!(with_text_aliases(html.clone()), html);
+    }
+}
@@ -1,18 +1,26 @@
 extern crate chrono;
 use std::cmp::PartialEq;
+use std::fs;
 use std::io;
 use std::path::Path;
+use std::time::UNIX_EPOCH;
 use chrono::offset::FixedOffset;
-use chrono::{Datelike, DateTime};
+use chrono::DateTime;
 use clipboard_rs::common::RustImage;
 use clipboard_rs::RustImageData;
-use log::debug;
+use lazy_static::lazy_static;
+use log::{debug, error, warn};
+use regex::Regex;
 use url::Url;
+use crate::core::blob_store::{BlobPin, BLOB_STORE, IMG_BLOB_EXT};
 use crate::core::clipboard::ClipboardHandle;
+use crate::core::dir_summary;
+use crate::core::large_text::{self, LARGE_TEXT_SIZE};
+use crate::core::source_app::SourceApp;
 use crate::utils;
-use crate::utils::config::CONFIG;
-use crate::utils::file::{format_size, get_file_size};
-use crate::utils::hash::hash_vec;
+use crate::db::entities::host_clipboard::{decode_paths, encode_paths};
+use crate::utils::file::{format_size, get_file_size_bytes};
+use crate::utils::hash::{hash_file, hash_str, hash_vec};
 use crate::utils::time::get_current_date_time;
 
 #[derive(Debug, Clone)]
@@ -20,6 +28,8 @@ pub enum ContentType {
     Text,
     Image,
     File,
+    RichText, // 同时保存纯文本, HTML 和 RTF
+    Directory, // 单个目录, 统计信息见 summary
 }
 
 impl ContentType {
@@ -28,22 +38,209 @@ impl ContentType {
             ContentType::Text => 0,
             ContentType::Image => 1,
             ContentType::File => 2,
+            ContentType::RichText => 3,
+            ContentType::Directory => 4,
         }
     }
 }
 
+// 文本条目的细分类型, 用于展示图标和对应的操作
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextSubtype {
+    Url,
+    Email,
+    FilePath,
+    Color, // #rrggbb 或 rgb()/rgba()
+    Json,
+    Uuid,
+    Ip,
+    Phone,
+    Timestamp, // 秒或毫秒级 Unix 时间戳
+    Code,      // 多行代码
+}
+
+impl TextSubtype {
+    pub fn to_i32(&self) -> i32 {
+        match self {
+            TextSubtype::Url => 0,
+            TextSubtype::Email => 1,
+            TextSubtype::FilePath => 2,
+            TextSubtype::Color => 3,
+            TextSubtype::Json => 4,
+            TextSubtype::Uuid => 5,
+            TextSubtype::Ip => 6,
+            TextSubtype::Phone => 7,
+            TextSubtype::Timestamp => 8,
+            TextSubtype::Code => 9,
+        }
+    }
+}
+
+lazy_static! {
+    static ref EMAIL_RE: Regex =
+        Regex::new(r"^[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}$").unwrap();
+    static ref UUID_RE: Regex = Regex::new(
+        r"^\{?[0-9A-Fa-f]{8}-[0-9A-Fa-f]{4}-[0-9A-Fa-f]{4}-[0-9A-Fa-f]{4}-[0-9A-Fa-f]{12}\}?$"
+    )
+    .unwrap();
+    static ref HEX_COLOR_RE: Regex =
+        Regex::new(r"^#([0-9A-Fa-f]{3}|[0-9A-Fa-f]{4}|[0-9A-Fa-f]{6}|[0-9A-Fa-f]{8})$").unwrap();
+    static ref RGB_COLOR_RE: Regex = Regex::new(
+        r"^(?i)rgba?\(\s*\d{1,3}%?\s*[, ]\s*\d{1,3}%?\s*[, ]\s*\d{1,3}%?\s*([,/]\s*(0|1|0?\.\d+|\d{1,3}%)\s*)?\)$"
+    )
+    .unwrap();
+    static ref PHONE_RE: Regex = Regex::new(r"^\+?[0-9][0-9 ()\-]{5,}[0-9]$").unwrap();
+    static ref WINDOWS_PATH_RE: Regex = Regex::new(r"^([A-Za-z]:[\\/]|\\\\[^\\/]+[\\/])").unwrap();
+}
+
+// 秒级时间戳的合理范围: 2000-01-01 到 2100-01-01
+const TIMESTAMP_RANGE: std::ops::Range<i64> = 946684800..4102444800;
+
+const CODE_KEYWORDS: [&str; 18] = [
+    "fn ", "def ", "class ", "import ", "from ", "return", "let ", "const ", "var ", "if ",
+    "for ", "while ", "function ", "#include", "package ", "use ", "pub ", "public ",
+];
+
+// 按内容判断文本的细分类型, 无法判断时返回 None
+pub fn classify_text(text: &str) -> Option<TextSubtype> {
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    if is_json(text) {
+        return Some(TextSubtype::Json);
+    }
+    if text.contains('\n') {
+        return is_code(text).then_some(TextSubtype::Code);
+    }
+
+    if UUID_RE.is_match(text) {
+        Some(TextSubtype::Uuid)
+    } else if HEX_COLOR_RE.is_match(text) || RGB_COLOR_RE.is_match(text) {
+        Some(TextSubtype::Color)
+    } else if is_url(text) {
+        Some(TextSubtype::Url)
+    } else if EMAIL_RE.is_match(text) {
+        Some(TextSubtype::Email)
+    } else if text.parse::<std::net::IpAddr>().is_ok() {
+        Some(TextSubtype::Ip)
+    } else if is_timestamp(text) {
+        Some(TextSubtype::Timestamp)
+    } else if is_phone(text) {
+        Some(TextSubtype::Phone)
+    } else if is_file_path(text) {
+        Some(TextSubtype::FilePath)
+    } else {
+        None
+    }
+}
+
+fn is_json(text: &str) -> bool {
+    (text.starts_with('{') || text.starts_with('['))
+        && serde_json::from_str::<serde_json::Value>(text).is_ok()
+}
+
+fn is_url(text: &str) -> bool {
+    !text.contains(char::is_whitespace)
+        && Url::parse(text)
+            .map(|url| matches!(url.scheme(), "http" | "https" | "ftp" | "ws" | "wss") && url.has_host())
+            .unwrap_or(false)
+}
+
+fn is_timestamp(text: &str) -> bool {
+    if !text.bytes().all(|b| b.is_ascii_digit()) {
+        return false;
+    }
+    let secs = match (text.len(), text.parse::<i64>()) {
+        (10, Ok(ts)) => ts,
+        (13, Ok(ts)) => ts / 1000,
+        _ => return false,
+    };
+    TIMESTAMP_RANGE.contains(&secs)
+}
+
+fn is_phone(text: &str) -> bool {
+    let digits = text.bytes().filter(|b| b.is_ascii_digit()).count();
+    PHONE_RE.is_match(text) && (7..=15).contains(&digits)
+}
+
+fn is_file_path(text: &str) -> bool {
+    let unix = (text.starts_with('/') || text.starts_with("~/")) && text.len() > 1;
+    (unix || WINDOWS_PATH_RE.is_match(text)) && !text.contains("//")
+}
+
+// 多数非空行以代码常见的符号结尾, 或带缩进, 或以关键字开头
+fn is_code(text: &str) -> bool {
+    let lines: Vec<&str> = text.lines().filter(|line| !line.trim().is_empty()).collect();
+    if lines.len() < 2 {
+        return false;
+    }
+    let code_lines = lines
+        .iter()
+        .filter(|line| {
+            let trimmed = line.trim();
+            line.starts_with("    ")
+                || line.starts_with('\t')
+                || trimmed.ends_with(|c| matches!(c, ';' | '{' | '}' | '(' | ')' | ':' | ','))
+                || CODE_KEYWORDS.iter().any(|kw| trimmed.starts_with(kw))
+        })
+        .count();
+    code_lines * 2 >= lines.len()
+}
+
 impl PartialEq for ContentType {
     fn eq(&self, other: &Self) -> bool {
         std::mem::discriminant(self) == std::mem::discriminant(other)
     }
 }
 
+// 常用格式的 MIME 类型
+pub const FORMAT_TEXT: &str = "text/plain";
+pub const FORMAT_HTML: &str = "text/html";
+pub const FORMAT_RTF: &str = "text/rtf";
+pub const FORMAT_URI_LIST: &str = "text/uri-list";
+pub const FORMAT_PNG: &str = "image/png";
+
+// 超过该大小的格式内容保存到 blob store, 数据库中只保存路径
+pub const REPRESENTATION_INLINE_LIMIT: usize = 64 * 1024;
+pub const REPRESENTATION_BLOB_EXT: &str = "bin";
+
+// 一次复制提供的一种格式及其内容
+#[derive(Debug, Clone)]
+pub struct Representation {
+    pub format: String,
+    pub data: Vec<u8>,
+    pub path: Option<String>, // 内容已在 blob store 中时为 blob 路径, data 为空
+    pub size: i64,
+}
+
+impl Representation {
+    pub fn new(format: String, data: Vec<u8>) -> Self {
+        let size = data.len() as i64;
+        Representation {
+            format,
+            data,
+            path: None,
+            size,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct PasteboardContent {
     pub text_content: String, // 索引内容
     pub r#type: ContentType,  // 类型
     pub hash: String,            // content or text_content hash
     pub path: String,         // 路径
+    pub size: i64,            // 字节数
+    pub source: Option<SourceApp>, // 来源应用, 未知时为 None
+    pub html: Option<String>, // 富文本的 HTML
+    pub rtf: Option<String>,  // 富文本的 RTF
+    pub representations: Vec<Representation>, // 来源提供的全部格式
+    pub subtype: Option<TextSubtype>, // 文本的细分类型
+    pub version_key: Option<String>, // 文件条目按路径分组, 同一文件的不同版本相同
+    pub summary: Option<String>, // 目录的统计信息, DirSummary 的 JSON
+    pub pins: Vec<BlobPin>,   // 本条记录写入的 blob, 入库后释放
     pub date_time: DateTime<FixedOffset>,
 }
 
@@ -54,15 +251,168 @@ impl PasteboardContent {
         content_type: ContentType,
         hash: String,
         path: Option<String>,
+        size: i64,
     ) -> Self {
         PasteboardContent {
             text_content,
             r#type: content_type,
             hash,
             path: path.unwrap_or_default(),
+            size,
+            source: None,
+            html: None,
+            rtf: None,
+            representations: Vec::new(),
+            subtype: None,
+            version_key: None,
+            summary: None,
+            pins: Vec::new(),
             date_time: get_current_date_time(),
         }
     }
+
+    // 文件, 目录和图片条目中的全部路径
+    pub fn paths(&self) -> Vec<String> {
+        decode_paths(&self.path)
+    }
+
+    pub fn is_large_text(&self) -> bool {
+        self.r#type == ContentType::Text && self.size as usize > LARGE_TEXT_SIZE
+    }
+
+    // 大文本压缩保存到 blob store, 记录中只保留预览, hash 仍为全文 hash
+    pub fn store_large_text(&mut self) -> io::Result<()> {
+        if !self.is_large_text() || !self.path.is_empty() {
+            return Ok(());
+        }
+        let pin = large_text::store(&self.hash, &self.text_content)?;
+        self.path = pin.path().to_str().unwrap().to_string();
+        self.pins.push(pin);
+        self.text_content = large_text::preview(&self.text_content).to_string();
+        Ok(())
+    }
+
+    // 多选中的目录按递归大小计入总大小, 耗时与目录大小有关, 在后台线程中执行
+    pub fn measure_directories(&mut self) -> io::Result<()> {
+        let paths = self.paths();
+        if self.r#type != ContentType::File || paths.len() < 2 {
+            return Ok(());
+        }
+        let mut dir_size = 0;
+        for path in paths.iter().filter(|p| Path::new(p).is_dir()) {
+            dir_size += dir_summary::summarize(path)?.total_size;
+        }
+        if dir_size > 0 {
+            self.size += dir_size as i64;
+            self.text_content = files_text_content(&paths, self.size as u64);
+        }
+        Ok(())
+    }
+
+    // 文件条目按内容重新计算 hash, 只改了修改时间的文件仍是同一版本;
+    // 可能读取大量文件内容, 在后台线程中执行
+    pub fn hash_file_versions(&mut self) {
+        if self.version_key.is_none() {
+            return;
+        }
+        let versions: Vec<String> = self.paths().iter().map(|p| file_version(p)).collect();
+        self.hash = hash_str(&encode_paths(&versions));
+    }
+
+    // 截图的 PNG 格式直接引用图片 blob, 其余大的格式写入 blob store;
+    // 在后台线程中拿数据库锁之前执行, 写入的 blob 在入库前保持 pin
+    pub fn store_representations(&mut self) {
+        let is_screenshot = self.r#type == ContentType::Image && BLOB_STORE.contains(&self.path);
+        let has_png = self
+            .representations
+            .iter()
+            .any(|rep| rep.format == FORMAT_PNG);
+        if is_screenshot && !has_png {
+            self.representations.push(Representation {
+                format: FORMAT_PNG.to_string(),
+                data: Vec::new(),
+                path: Some(self.path.clone()),
+                size: fs::metadata(&self.path).map_or(0, |m| m.len() as i64),
+            });
+        }
+
+        let mut pins = Vec::new();
+        self.representations.retain_mut(|rep| {
+            if rep.path.is_some() || rep.data.len() <= REPRESENTATION_INLINE_LIMIT {
+                return true;
+            }
+            match BLOB_STORE.put_pinned(&hash_vec(&rep.data), REPRESENTATION_BLOB_EXT, &rep.data) {
+                Ok(pin) => {
+                    rep.path = Some(pin.path().to_str().unwrap().to_string());
+                    rep.data = Vec::new();
+                    pins.push(pin);
+                    true
+                }
+                Err(e) => {
+                    error!("Failed to store {} blob: {}", rep.format, e);
+                    false
+                }
+            }
+        });
+        self.pins.extend(pins);
+    }
+
+    // 递归统计目录, 耗时与目录大小有关, 在后台线程中执行
+    pub fn summarize_directory(&mut self) -> io::Result<()> {
+        if self.r#type != ContentType::Directory || self.summary.is_some() {
+            return Ok(());
+        }
+        let summary = dir_summary::summarize(&self.path)?;
+        self.text_content = format!(
+            "Dir: {} ({} files, {}{})",
+            self.path,
+            summary.file_count,
+            format_size(summary.total_size as usize),
+            if summary.truncated { "+" } else { "" }
+        );
+        self.size = summary.total_size as i64;
+        self.summary = Some(serde_json::to_string(&summary)?);
+        Ok(())
+    }
+
+    // 未能读取到原始格式时, 根据记录内容生成
+    pub fn fallback_representations(&self) -> Vec<Representation> {
+        let mut representations = Vec::new();
+        let mut push = |format: &str, data: &str| {
+            representations.push(Representation::new(
+                format.to_string(),
+                data.as_bytes().to_vec(),
+            ))
+        };
+        match self.r#type {
+            // 大文本粘贴时从 blob store 读取全文
+            ContentType::Text if self.is_large_text() => {}
+            ContentType::Text => push(FORMAT_TEXT, &self.text_content),
+            ContentType::RichText => {
+                if let Some(html) = &self.html {
+                    push(FORMAT_HTML, html);
+                }
+                if let Some(rtf) = &self.rtf {
+                    push(FORMAT_RTF, rtf);
+                }
+                push(FORMAT_TEXT, &self.text_content);
+            }
+            // 图片与文件一样以文件列表的方式粘贴
+            ContentType::Image | ContentType::File | ContentType::Directory => {
+                let uri_list = self
+                    .paths()
+                    .into_iter()
+                    .filter_map(|path| Url::from_file_path(path).ok())
+                    .map(|url| url.to_string())
+                    .collect::<Vec<_>>()
+                    .join("\r\n");
+                if !uri_list.is_empty() {
+                    push(FORMAT_URI_LIST, &uri_list);
+                }
+            }
+        }
+        representations
+    }
 }
 
 
@@ -77,12 +427,49 @@ impl ClipboardHandle {
             return None;
         }
         self.last_hash = hash.clone();
-        return Some(PasteboardContent::new(
+        let size = text_content.len() as i64;
+        let subtype = classify_text(&text_content);
+        let mut content = PasteboardContent::new(text_content, ContentType::Text, hash, None, size);
+        content.subtype = subtype;
+        Some(content)
+    }
+
+    // 纯文本用于展示和搜索, 粘贴时优先使用 HTML/RTF
+    pub(crate) fn new_rich_text_content(
+        &mut self,
+        text_content: String,
+        html: Option<String>,
+        rtf: Option<String>,
+    ) -> Option<PasteboardContent> {
+        let size = text_content.len()
+            + html.as_ref().map_or(0, |h| h.len())
+            + rtf.as_ref().map_or(0, |r| r.len());
+        if text_content.trim().is_empty() {
+            return None;
+        }
+        // 大富文本只保留纯文本
+        if size > LARGE_TEXT_SIZE {
+            return self.new_text_content(text_content);
+        }
+
+        // 相同文本的纯文本和富文本分别记录
+        let hash = utils::hash::hash_str(&format!(
+            "{}\0{}\0{}",
             text_content,
-            ContentType::Text,
-            hash,
-            None,
+            html.as_deref().unwrap_or_default(),
+            rtf.as_deref().unwrap_or_default()
         ));
+        if self.check_hash(&hash) {
+            return None;
+        }
+        self.last_hash = hash.clone();
+        let subtype = classify_text(&text_content);
+        let mut content =
+            PasteboardContent::new(text_content, ContentType::RichText, hash, None, size as i64);
+        content.subtype = subtype;
+        content.html = html;
+        content.rtf = rtf;
+        Some(content)
     }
 
     pub(crate) fn new_file_content(&mut self, file_url: String) -> Option<PasteboardContent> {
@@ -93,32 +480,83 @@ impl ClipboardHandle {
             .iter()
             .any(|&ext| ext == file_end.to_lowercase());
 
-        let url = Url::parse(&*file_url).expect("Invalid URL");
-        let path_str = url.to_file_path().unwrap().to_str().unwrap().to_string();
-        let hash = utils::hash::hash_str(&path_str);
+        let path_str = file_url_to_path(&file_url)?;
+        // 先按修改时间去重, 按内容计算的 hash 在后台线程中替换
+        let hash = hash_str(&file_stamp(&path_str));
 
         if self.check_hash(&hash) {
             return None;
         }
         self.last_hash = hash.clone();
 
-        return if is_image {
-            let text_content = format!("Img: {} ({})", path_str, get_file_size(&path_str));
-            Some(PasteboardContent::new(
+        let size = get_file_size_bytes(&path_str);
+        let version_key = hash_str(&path_str);
+        let mut content = if Path::new(&path_str).is_dir() {
+            // 大小和文件数在后台统计
+            let text_content = format!("Dir: {}", path_str);
+            PasteboardContent::new(text_content, ContentType::Directory, hash, Some(path_str), 0)
+        } else if is_image {
+            let text_content = format!("Img: {} ({})", path_str, format_size(size as usize));
+            PasteboardContent::new(
                 text_content,
                 ContentType::Image,
                 hash,
                 Some(path_str),
-            ))
+                size as i64,
+            )
         } else {
-            let text_content = format!("File: {} ({})", path_str, get_file_size(&path_str));
-            Some(PasteboardContent::new(
+            let text_content = format!("File: {} ({})", path_str, format_size(size as usize));
+            PasteboardContent::new(
                 text_content,
                 ContentType::File,
                 hash,
                 Some(path_str),
-            ))
+                size as i64,
+            )
         };
+        content.version_key = Some(version_key);
+        Some(content)
+    }
+
+    pub(crate) fn new_files_content(&mut self, file_urls: Vec<String>) -> Option<PasteboardContent> {
+        if file_urls.len() == 1 {
+            return self.new_file_content(file_urls.into_iter().next().unwrap());
+        }
+
+        // 多文件按复制时的顺序保存为一条记录
+        let paths: Vec<String> = file_urls
+            .iter()
+            .filter_map(|f_url| file_url_to_path(f_url))
+            .collect();
+        if paths.is_empty() {
+            return None;
+        }
+        let path = encode_paths(&paths);
+        let stamps: Vec<String> = paths.iter().map(|p| file_stamp(p)).collect();
+        let hash = hash_str(&encode_paths(&stamps));
+
+        if self.check_hash(&hash) {
+            return None;
+        }
+        self.last_hash = hash.clone();
+
+        // 目录的大小在后台统计
+        let total_size: u64 = paths
+            .iter()
+            .filter(|p| !Path::new(p).is_dir())
+            .map(|p| get_file_size_bytes(p))
+            .sum();
+        let text_content = files_text_content(&paths, total_size);
+        let version_key = hash_str(&path);
+        let mut content = PasteboardContent::new(
+            text_content,
+            ContentType::File,
+            hash,
+            Some(path),
+            total_size as i64,
+        );
+        content.version_key = Some(version_key);
+        Some(content)
     }
 
     pub(crate) fn new_img_content(&mut self, img: &RustImageData) -> Option<PasteboardContent> {
@@ -130,18 +568,30 @@ impl ClipboardHandle {
             format_size(img.get_bytes().len())
         );
         let hash = hash_vec(img.get_bytes());
-        let path = get_local_path("png").unwrap();
-        img.save_to_path(&path).unwrap();
         if self.check_hash(&hash) {
             return None;
         }
+        // 同一张图片只落盘一次, 重复复制时复用已有 blob
+        let pin = match BLOB_STORE.put_with_pinned(&hash, IMG_BLOB_EXT, |tmp| {
+            img.save_to_path(tmp.to_str().unwrap())
+                .map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+        }) {
+            Ok(pin) => pin,
+            Err(e) => {
+                error!("Failed to store image blob: {}", e);
+                return None;
+            }
+        };
         self.last_hash = hash.clone();
-        Some(PasteboardContent::new(
+        let mut content = PasteboardContent::new(
             text_content,
             ContentType::Image,
             hash,
-            Some(path),
-        ))
+            Some(pin.path().to_str().unwrap().to_string()),
+            img.get_bytes().len() as i64,
+        );
+        content.pins.push(pin);
+        Some(content)
     }
 
     fn check_hash(&self, hash: &str) -> bool {
@@ -154,32 +604,170 @@ impl ClipboardHandle {
     }
 }
 
+// 不超过该大小的文件按内容计算 hash, 更大的文件使用大小和修改时间
+const CONTENT_HASH_LIMIT: u64 = 16 * 1024 * 1024;
 
-fn get_local_path(suffix: &str) -> Result<String, io::Error> {
-    let date_time = get_current_date_time();
-    let root_file_path = CONFIG
-        .read()
-        .unwrap()
-        .files_path
-        .join(format!(
-            "{}{}{}",
-            date_time.year(),
-            date_time.month(),
-            date_time.day()
-        ))
-        .to_str()
-        .unwrap()
-        .to_string();
-    // 判断root_file_path 是否存在 不存在则递归创建
-    if !Path::new(&root_file_path).exists() {
-        std::fs::create_dir_all(&root_file_path).unwrap_or_else(|e| {
-            panic!("Failed to create directories: {}", e);
-        });
+// 文件当前版本的标识, 内容变化后视为新版本; 会读取文件内容, 只在后台线程中调用
+fn file_version(path: &str) -> String {
+    match fs::metadata(path) {
+        Ok(metadata) if metadata.is_file() && metadata.len() <= CONTENT_HASH_LIMIT => {
+            match hash_file(path) {
+                Ok(hash) => return format!("{}\0{}", path, hash),
+                Err(e) => error!("Failed to hash file {}: {}", path, e),
+            }
+        }
+        Err(_) => return path.to_string(),
+        _ => {}
+    }
+    file_stamp(path)
+}
+
+// 只读取元数据的版本标识, 用于监听线程中的去重
+fn file_stamp(path: &str) -> String {
+    let metadata = match fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(_) => return path.to_string(),
+    };
+    let mtime = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_nanos());
+    format!("{}\0{}\0{}", path, metadata.len(), mtime)
+}
+
+fn files_text_content(paths: &[String], total_size: u64) -> String {
+    format!(
+        "Files: {} items ({})\n{}",
+        paths.len(),
+        format_size(total_size as usize),
+        paths.join("\n")
+    )
+}
+
+// 非 file:// 或非 UTF-8 的路径返回 None, 由调用方跳过
+fn file_url_to_path(file_url: &str) -> Option<String> {
+    let path = Url::parse(file_url)
+        .ok()
+        .and_then(|url| url.to_file_path().ok())
+        .and_then(|path| path.to_str().map(str::to_string));
+    if path.is_none() {
+        warn!("Skip unsupported file url: {}", file_url);
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_single_line() {
+        let cases = [
+            ("https://example.com/a?b=1", Some(TextSubtype::Url)),
+            ("  http://localhost:8080 \n", Some(TextSubtype::Url)),
+            ("user.name+tag@example.co.uk", Some(TextSubtype::Email)),
+            ("/usr/local/bin", Some(TextSubtype::FilePath)),
+            ("~/.config/super-cv", Some(TextSubtype::FilePath)),
+            (r"C:\Users\me\a.txt", Some(TextSubtype::FilePath)),
+            ("#1e90ff", Some(TextSubtype::Color)),
+            ("rgba(30, 144, 255, 0.5)", Some(TextSubtype::Color)),
+            (r#"{"a": [1, 2]}"#, Some(TextSubtype::Json)),
+            ("67e55044-10b1-426f-9247-bb680e5fe0c8", Some(TextSubtype::Uuid)),
+            ("192.168.1.1", Some(TextSubtype::Ip)),
+            ("::1", Some(TextSubtype::Ip)),
+            ("+86 138-0013-8000", Some(TextSubtype::Phone)),
+            ("13800138000", Some(TextSubtype::Phone)),
+            ("1724659200", Some(TextSubtype::Timestamp)),
+            ("1724659200123", Some(TextSubtype::Timestamp)),
+            ("hello world", None),
+            ("#hashtag", None),
+            ("{not json", None),
+            ("42", None),
+            ("/", None),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(classify_text(text), expected, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn test_classify_multi_line() {
+        let json = "{\n  \"name\": \"super-cv\",\n  \"tags\": []\n}";
+        assert_eq!(classify_text(json), Some(TextSubtype::Json));
+
+        let rust = "fn main() {\n    println!(\"hi\");\n}";
+        assert_eq!(classify_text(rust), Some(TextSubtype::Code));
+
+        let python = "def f(x):\n    return x + 1\n";
+        assert_eq!(classify_text(python), Some(TextSubtype::Code));
+
+        let prose = "Dear team,\nthe meeting is moved to Friday.\nThanks";
+        assert_eq!(classify_text(prose), None);
+    }
+
+    #[test]
+    fn test_file_url_to_path() {
+        #[cfg(unix)]
+        assert_eq!(
+            file_url_to_path("file:///tmp/a%20b.txt"),
+            Some("/tmp/a b.txt".to_string())
+        );
+        assert_eq!(file_url_to_path("https://example.com/a.txt"), None);
+        assert_eq!(file_url_to_path("not a url"), None);
+    }
+
+    #[test]
+    fn test_paths_with_newlines() {
+        let paths = vec!["/tmp/a\nb.txt".to_string(), "/tmp/c.txt".to_string()];
+        let content = PasteboardContent::new(
+            files_text_content(&paths, 0),
+            ContentType::File,
+            "h".to_string(),
+            Some(encode_paths(&paths)),
+            0,
+        );
+        assert_eq!(content.paths(), paths);
+
+        let single = vec!["/tmp/a\nb.txt".to_string()];
+        assert_eq!(encode_paths(&single), "/tmp/a\nb.txt");
+        assert_eq!(decode_paths(&encode_paths(&single)), single);
+        assert!(decode_paths("").is_empty());
+    }
+
+    #[test]
+    fn test_measure_directories() {
+        let root = std::env::temp_dir().join(format!("super-cv-measure-{}", std::process::id()));
+        fs::create_dir_all(root.join("dir")).unwrap();
+        fs::write(root.join("dir").join("a"), "12345").unwrap();
+        fs::write(root.join("b"), "123").unwrap();
+        let paths = [root.join("dir"), root.join("b")].map(|p| p.to_str().unwrap().to_string());
+
+        let mut content = PasteboardContent::new(
+            files_text_content(&paths, 3),
+            ContentType::File,
+            "h".to_string(),
+            Some(encode_paths(&paths)),
+            3,
+        );
+        content.measure_directories().unwrap();
+        assert_eq!(content.size, 8);
+        assert!(content.text_content.starts_with("Files: 2 items (8"));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_file_version() {
+        let path = std::env::temp_dir().join(format!("super-cv-version-{}", std::process::id()));
+        let path_str = path.to_str().unwrap();
+        fs::write(&path, "v1").unwrap();
+        let v1 = file_version(path_str);
+        assert_eq!(file_version(path_str), v1);
+        fs::write(&path, "v2").unwrap();
+        assert_ne!(file_version(path_str), v1);
+        fs::write(&path, "v1").unwrap();
+        assert_eq!(file_version(path_str), v1);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(file_version(path_str), path_str);
     }
-    Ok(format!(
-        "{}/{}.{}",
-        root_file_path,
-        date_time.timestamp(),
-        suffix
-    ))
 }
@@ -0,0 +1,363 @@
+use crate::core::pasteboard::ContentType;
+use crate::utils::file::expand_home;
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveTime, TimeZone};
+use serde::Serialize;
+
+// 搜索框的查询语法:
+//   foo "foo bar" -foo          关键词 / 短语 / 排除
+//   type:img,file               类型 text|img|file|dir|rich
+//   after:2024-07-01 before:yesterday   日期 YYYY-MM-DD|today|yesterday|7d|12h|2w
+//   size:>1MB                   大小 > >= < <= = 以及 B/KB/MB/GB
+//   path:~/Downloads            路径包含
+//   tag:sql                     标签
+//   app:terminal                来源应用或窗口标题包含
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SearchQuery {
+    pub terms: Vec<SearchTerm>,
+    pub types: Vec<i32>,
+    pub exclude_types: Vec<i32>,
+    pub after: Option<i64>,
+    pub before: Option<i64>,
+    pub sizes: Vec<(SizeCmp, i64)>,
+    pub paths: Vec<String>,
+    pub exclude_paths: Vec<String>,
+    pub tags: Vec<String>,
+    pub exclude_tags: Vec<String>,
+    pub apps: Vec<String>,
+    pub exclude_apps: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchTerm {
+    pub text: String,
+    pub phrase: bool,
+    pub negated: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SizeCmp {
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Eq,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct QueryError {
+    pub message: String,
+    pub token: String,
+    pub offset: usize, // token 在查询中的位置, 以字符计
+}
+
+impl std::fmt::Display for QueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (at {}: {})", self.message, self.offset, self.token)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+struct Token {
+    text: String,
+    offset: usize,
+    quoted: bool,
+    negated: bool,
+}
+
+pub fn parse(input: &str) -> Result<SearchQuery, QueryError> {
+    parse_at(input, Local::now())
+}
+
+fn parse_at(input: &str, now: DateTime<Local>) -> Result<SearchQuery, QueryError> {
+    let mut query = SearchQuery::default();
+    for token in tokenize(input)? {
+        let filter = if token.quoted {
+            None
+        } else {
+            token
+                .text
+                .split_once(':')
+                .filter(|(key, _)| FILTER_KEYS.contains(key))
+        };
+        let Some((key, value)) = filter else {
+            query.terms.push(SearchTerm {
+                text: token.text,
+                phrase: token.quoted,
+                negated: token.negated,
+            });
+            continue;
+        };
+
+        let value = value.trim_matches('"');
+        if value.is_empty() {
+            return Err(error(&token, format!("missing value for {}:", key)));
+        }
+        match key {
+            "type" => {
+                let types = value
+                    .split(',')
+                    .map(|t| {
+                        parse_type(t).ok_or_else(|| error(&token, format!("unknown type: {}", t)))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                if token.negated {
+                    query.exclude_types.extend(types);
+                } else {
+                    query.types.extend(types);
+                }
+            }
+            "path" => {
+                let path = expand_home(value);
+                if token.negated {
+                    query.exclude_paths.push(path);
+                } else {
+                    query.paths.push(path);
+                }
+            }
+            "tag" => {
+                if token.negated {
+                    query.exclude_tags.push(value.to_string());
+                } else {
+                    query.tags.push(value.to_string());
+                }
+            }
+            "app" => {
+                if token.negated {
+                    query.exclude_apps.push(value.to_string());
+                } else {
+                    query.apps.push(value.to_string());
+                }
+            }
+            _ if token.negated => {
+                return Err(error(&token, format!("{}: can not be negated", key)));
+            }
+            "after" => {
+                let ts = parse_date(value, now)
+                    .ok_or_else(|| error(&token, format!("invalid date: {}", value)))?;
+                query.after = Some(ts);
+            }
+            "before" => {
+                let ts = parse_date(value, now)
+                    .ok_or_else(|| error(&token, format!("invalid date: {}", value)))?;
+                query.before = Some(ts);
+            }
+            "size" => {
+                let size = parse_size(value)
+                    .ok_or_else(|| error(&token, format!("invalid size: {}", value)))?;
+                query.sizes.push(size);
+            }
+            _ => unreachable!(),
+        }
+    }
+    Ok(query)
+}
+
+const FILTER_KEYS: [&str; 7] = ["type", "after", "before", "size", "path", "tag", "app"];
+
+fn error(token: &Token, message: String) -> QueryError {
+    QueryError {
+        message,
+        token: token.text.clone(),
+        offset: token.offset,
+    }
+}
+
+// 按空白切分, 双引号内的空白不切分; 前导 '-' 表示排除
+fn tokenize(input: &str) -> Result<Vec<Token>, QueryError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i].is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let offset = i;
+        let negated = chars[i] == '-' && i + 1 < chars.len() && !chars[i + 1].is_whitespace();
+        if negated {
+            i += 1;
+        }
+
+        let quoted = chars[i] == '"';
+        let mut text = String::new();
+        let mut in_quote = false;
+        while i < chars.len() && (in_quote || !chars[i].is_whitespace()) {
+            if chars[i] == '"' {
+                in_quote = !in_quote;
+                // 只有整个 token 被引号包裹时才去掉引号, key:"value" 的引号由过滤器处理
+                if !quoted {
+                    text.push('"');
+                }
+            } else {
+                text.push(chars[i]);
+            }
+            i += 1;
+        }
+        if in_quote {
+            return Err(QueryError {
+                message: "unterminated quote".to_string(),
+                token: chars[offset..].iter().collect(),
+                offset,
+            });
+        }
+        if !text.is_empty() {
+            tokens.push(Token {
+                text,
+                offset,
+                quoted,
+                negated,
+            });
+        }
+    }
+    Ok(tokens)
+}
+
+fn parse_type(value: &str) -> Option<i32> {
+    match value.to_lowercase().as_str() {
+        "text" | "txt" => Some(ContentType::Text.to_i32()),
+        "img" | "image" => Some(ContentType::Image.to_i32()),
+        "file" => Some(ContentType::File.to_i32()),
+        "dir" | "folder" => Some(ContentType::Directory.to_i32()),
+        "rich" | "html" => Some(ContentType::RichText.to_i32()),
+        _ => None,
+    }
+}
+
+// 日期取当天 0 点(本地时区), 相对时间从 now 往前推
+fn parse_date(value: &str, now: DateTime<Local>) -> Option<i64> {
+    let day_start = |date: NaiveDate| {
+        Local
+            .from_local_datetime(&date.and_time(NaiveTime::MIN))
+            .earliest()
+            .map(|dt| dt.timestamp())
+    };
+    let today = now.date_naive();
+    match value.to_lowercase().as_str() {
+        "today" => return day_start(today),
+        "yesterday" => return day_start(today - Duration::days(1)),
+        _ => {}
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return day_start(date);
+    }
+
+    let unit_start = value.len() - value.chars().last()?.len_utf8();
+    let (num, unit) = value.split_at(unit_start);
+    let num: i64 = num.parse().ok()?;
+    // 数字来自搜索框, 超出范围时作为解析错误而不是 panic
+    let duration = match unit {
+        "h" => Duration::try_hours(num),
+        "d" => Duration::try_days(num),
+        "w" => Duration::try_weeks(num),
+        _ => return None,
+    }?;
+    now.checked_sub_signed(duration).map(|dt| dt.timestamp())
+}
+
+fn parse_size(value: &str) -> Option<(SizeCmp, i64)> {
+    let (cmp, rest) = if let Some(rest) = value.strip_prefix(">=") {
+        (SizeCmp::Ge, rest)
+    } else if let Some(rest) = value.strip_prefix("<=") {
+        (SizeCmp::Le, rest)
+    } else if let Some(rest) = value.strip_prefix('>') {
+        (SizeCmp::Gt, rest)
+    } else if let Some(rest) = value.strip_prefix('<') {
+        (SizeCmp::Lt, rest)
+    } else {
+        (SizeCmp::Eq, value.strip_prefix('=').unwrap_or(value))
+    };
+
+    let rest = rest.to_uppercase();
+    let unit_start = rest
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(rest.len());
+    let (num, unit) = rest.split_at(unit_start);
+    let num: f64 = num.parse().ok()?;
+    let unit: i64 = match unit {
+        "" | "B" => 1,
+        "K" | "KB" => 1024,
+        "M" | "MB" => 1024 * 1024,
+        "G" | "GB" => 1024 * 1024 * 1024,
+        _ => return None,
+    };
+    Some((cmp, (num * unit as f64) as i64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 7, 15, 12, 0, 0).unwrap()
+    }
+
+    fn day(y: i32, m: u32, d: u32) -> i64 {
+        Local
+            .with_ymd_and_hms(y, m, d, 0, 0, 0)
+            .unwrap()
+            .timestamp()
+    }
+
+    #[test]
+    fn test_parse_terms() {
+        let query = parse_at(r#"foo "bar baz" -qux http://a.b"#, now()).unwrap();
+        let terms: Vec<_> = query
+            .terms
+            .iter()
+            .map(|t| (t.text.as_str(), t.phrase, t.negated))
+            .collect();
+        assert_eq!(
+            terms,
+            vec![
+                ("foo", false, false),
+                ("bar baz", true, false),
+                ("qux", false, true),
+                ("http://a.b", false, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_filters() {
+        let query = parse_at(
+            "type:img,file -type:text after:2024-07-01 before:yesterday size:>1.5MB path:\"/tmp/a b\" tag:sql -tag:old app:Terminal -app:firefox",
+            now(),
+        )
+        .unwrap();
+        assert_eq!(query.types, vec![1, 2]);
+        assert_eq!(query.exclude_types, vec![0]);
+        assert_eq!(query.after, Some(day(2024, 7, 1)));
+        assert_eq!(query.before, Some(day(2024, 7, 14)));
+        assert_eq!(query.sizes, vec![(SizeCmp::Gt, 1572864)]);
+        assert_eq!(query.paths, vec!["/tmp/a b".to_string()]);
+        assert_eq!(query.tags, vec!["sql".to_string()]);
+        assert_eq!(query.exclude_tags, vec!["old".to_string()]);
+        assert_eq!(query.apps, vec!["Terminal".to_string()]);
+        assert_eq!(query.exclude_apps, vec!["firefox".to_string()]);
+        assert!(query.terms.is_empty());
+    }
+
+    #[test]
+    fn test_parse_relative_date() {
+        let query = parse_at("after:7d", now()).unwrap();
+        assert_eq!(query.after, Some((now() - Duration::days(7)).timestamp()));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = parse_at("foo type:video", now()).unwrap_err();
+        assert_eq!(err.offset, 4);
+        assert_eq!(err.token, "type:video");
+
+        assert!(parse_at("size:>lots", now()).is_err());
+        assert!(parse_at("after:someday", now()).is_err());
+        assert!(parse_at("after:99999999999999d", now()).is_err());
+        assert!(parse_at("before:9999999999999w", now()).is_err());
+        assert!(parse_at("after:99999999999999999999h", now()).is_err());
+        assert!(parse_at("-after:today", now()).is_err());
+        assert!(parse_at("\"open phrase", now()).is_err());
+        assert!(parse_at("path:", now()).is_err());
+    }
+}
@@ -0,0 +1,135 @@
+use crate::core::blob_store::BLOB_STORE;
+use crate::core::pasteboard::ContentType;
+use crate::core::thumbnail;
+use crate::db::crud;
+use crate::time_it;
+use crate::utils::config::CONFIG;
+use crate::utils::file::format_size;
+use log::{debug, error, info};
+use sea_orm::{DatabaseConnection, DbErr};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+// 定时清理的间隔
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Serialize, Debug, Default, Clone)]
+pub struct PurgeReport {
+    pub rows: u64,  // 删除的记录数
+    pub files: u64, // 删除的文件数
+    pub bytes: u64, // 释放的磁盘空间
+    pub dirs: u64,  // 删除的空目录数
+}
+
+// 启动时执行一次, 之后按 PURGE_INTERVAL 定时清理过期记录
+pub fn spawn_retention_worker(db: Arc<Mutex<DatabaseConnection>>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PURGE_INTERVAL);
+        loop {
+            interval.tick().await;
+            let db_guard = db.lock().await;
+            if let Err(e) = time_it!(async purge_expired(&db_guard)).await {
+                error!("purge expired clipboards err: {:?}", e);
+            }
+        }
+    });
+}
+
+// 删除过期记录以及只被这些记录引用的文件
+pub async fn purge_expired(db: &DatabaseConnection) -> Result<PurgeReport, DbErr> {
+    let (expired, blob_paths) = crud::host_clipboard::delete_expired_entries(db).await?;
+    let mut report = PurgeReport {
+        rows: expired.len() as u64,
+        ..Default::default()
+    };
+
+    let files_path = CONFIG.read().unwrap().files_path.clone();
+    let paths: HashSet<&str> = expired
+        .iter()
+        .map(|entry| entry.path.as_str())
+        .chain(blob_paths.iter().map(|path| path.as_str()))
+        .collect();
+    for path in paths {
+        // 只处理 SuperCV 自己保存的文件, 用户复制的原文件不动
+        if !Path::new(path).starts_with(&files_path) {
+            continue;
+        }
+        let freed = if BLOB_STORE.contains(path) {
+            crud::host_clipboard::release_blob(db, path).await?
+        } else {
+            release_legacy_file(db, path).await?
+        };
+        if freed > 0 {
+            report.files += 1;
+            report.bytes += freed;
+        }
+    }
+
+    // 缩略图只属于对应的图片记录
+    for entry in expired.iter().filter(|entry| entry.r#type == ContentType::Image.to_i32()) {
+        let freed = thumbnail::remove(&entry.hash);
+        if freed > 0 {
+            report.files += 1;
+            report.bytes += freed;
+        }
+    }
+
+    report.dirs = remove_empty_dirs(&files_path).unwrap_or_else(|e| {
+        error!("Failed to clean up {}: {}", files_path.display(), e);
+        0
+    });
+
+    if report.rows > 0 || report.dirs > 0 {
+        info!(
+            "purged {} expired clipboards, {} files ({}), {} empty dirs",
+            report.rows,
+            report.files,
+            format_size(report.bytes as usize),
+            report.dirs
+        );
+    } else {
+        debug!("no expired clipboards to purge");
+    }
+    Ok(report)
+}
+
+// 旧版本按日期目录(files/YYYYMD)保存的图片
+async fn release_legacy_file(db: &DatabaseConnection, path: &str) -> Result<u64, DbErr> {
+    if crud::host_clipboard::count_path_refs(db, path).await? > 0 {
+        return Ok(0);
+    }
+    let size = match fs::metadata(path) {
+        Ok(metadata) => metadata.len(),
+        Err(_) => return Ok(0),
+    };
+    match fs::remove_file(path) {
+        Ok(()) => Ok(size),
+        Err(e) => {
+            error!("Failed to remove {}: {}", path, e);
+            Ok(0)
+        }
+    }
+}
+
+// 递归删除 root 下的空目录(不包含 root 本身), 返回删除的目录数
+fn remove_empty_dirs(root: &Path) -> io::Result<u64> {
+    let mut removed = 0;
+    for entry in fs::read_dir(root)? {
+        let path = entry?.path();
+        if !path.is_dir() {
+            continue;
+        }
+        removed += remove_empty_dirs(&path)?;
+        // 由 blob store 判断, 不删除正在写入 blob 的分片目录
+        if path != BLOB_STORE.root() && BLOB_STORE.remove_empty_dir(&path)? {
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
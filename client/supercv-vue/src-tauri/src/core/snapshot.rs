@@ -0,0 +1,90 @@
+use crate::core::blob_store::{BlobPin, BLOB_STORE};
+use crate::utils::hash::hash_vec;
+use log::debug;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const SNAPSHOT_BLOB_EXT: &str = "snap";
+
+// 一个文件的副本
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub original_path: String,
+    pub blob_path: String,
+    pub size: i64,
+    pub pin: BlobPin, // 保存到数据库之前不被清理
+}
+
+// 复制不超过 max_size 的普通文件到 blob store, 目录和超过上限的文件返回 None
+pub fn take(path: &str, max_size: u64) -> io::Result<Option<Snapshot>> {
+    let metadata = fs::metadata(path)?;
+    if !metadata.is_file() || metadata.len() > max_size {
+        debug!("skip snapshot of {} ({} bytes)", path, metadata.len());
+        return Ok(None);
+    }
+    let bytes = fs::read(path)?;
+    let pin = BLOB_STORE.put_pinned(&hash_vec(&bytes), SNAPSHOT_BLOB_EXT, &bytes)?;
+    Ok(Some(Snapshot {
+        original_path: path.to_string(),
+        blob_path: pin.path().to_str().unwrap().to_string(),
+        size: bytes.len() as i64,
+        pin,
+    }))
+}
+
+// 以原文件名还原到临时目录, 粘贴后目标应用看到的仍是原来的文件名
+pub fn restore(blob_path: &str, original_path: &str) -> io::Result<PathBuf> {
+    let name = Path::new(original_path)
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?;
+    let blob_name = Path::new(blob_path).file_stem().unwrap_or_default();
+    let dir = restore_dir().join(blob_name);
+    let target = dir.join(name);
+    // 上次还原的文件可能已被用户修改, 内容一致时才复用
+    if is_same_file(&target, blob_path)? {
+        return Ok(target);
+    }
+    fs::create_dir_all(&dir)?;
+    // 不使用硬链接, 避免粘贴后修改文件时改动 blob
+    fs::copy(blob_path, &target)?;
+    Ok(target)
+}
+
+fn is_same_file(target: &Path, blob_path: &str) -> io::Result<bool> {
+    let target_len = match fs::metadata(target) {
+        Ok(metadata) if metadata.is_file() => metadata.len(),
+        _ => return Ok(false),
+    };
+    if target_len != fs::metadata(blob_path)?.len() {
+        return Ok(false);
+    }
+    Ok(fs::read(target)? == fs::read(blob_path)?)
+}
+
+fn restore_dir() -> PathBuf {
+    std::env::temp_dir().join("super-cv").join("restored")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restore_replaces_edited_copy() {
+        let blob = std::env::temp_dir().join(format!("super-cv-snap-{}.snap", std::process::id()));
+        fs::write(&blob, "snapshot").unwrap();
+        let blob_path = blob.to_str().unwrap();
+
+        let target = restore(blob_path, "/home/me/notes.txt").unwrap();
+        assert_eq!(target.file_name().unwrap(), "notes.txt");
+        assert_eq!(fs::read_to_string(&target).unwrap(), "snapshot");
+
+        fs::write(&target, "edited").unwrap();
+        assert_eq!(restore(blob_path, "/home/me/notes.txt").unwrap(), target);
+        assert_eq!(fs::read_to_string(&target).unwrap(), "snapshot");
+
+        fs::remove_dir_all(target.parent().unwrap()).unwrap();
+        fs::remove_file(&blob).unwrap();
+    }
+}
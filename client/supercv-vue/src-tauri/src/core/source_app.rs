@@ -0,0 +1,137 @@
+// 记录剪贴板内容来自哪个应用
+// 目前只支持 X11: 优先取 CLIPBOARD 的 selection owner, 取不到时退回当前焦点窗口
+// 其他平台以及纯 Wayland 会话返回 None, 入库为 NULL
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SourceApp {
+    pub app: Option<String>,      // WM_CLASS 中的类名, 如 Gnome-terminal
+    pub title: Option<String>,    // 窗口标题
+    pub instance: Option<String>, // WM_CLASS 中的实例名, 如 gnome-terminal, 只用于忽略规则
+}
+
+#[cfg(target_os = "linux")]
+pub fn current() -> Option<SourceApp> {
+    match x11::current() {
+        Ok(source) => source,
+        Err(e) => {
+            log::debug!("Failed to get source app: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn current() -> Option<SourceApp> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+mod x11 {
+    use super::SourceApp;
+    use lazy_static::lazy_static;
+    use std::error::Error;
+    use std::sync::Mutex;
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{Atom, AtomEnum, ConnectionExt, Window};
+    use x11rb::rust_connection::RustConnection;
+    use x11rb::NONE;
+
+    lazy_static! {
+        // 每次剪贴板变化都在监听线程中查询, 复用同一个连接; 出错后断开, 下次重新连接
+        static ref CONNECTION: Mutex<Option<(RustConnection, usize)>> = Mutex::new(None);
+    }
+
+    pub(super) fn current() -> Result<Option<SourceApp>, Box<dyn Error>> {
+        let mut connection = CONNECTION.lock().unwrap();
+        if connection.is_none() {
+            *connection = Some(x11rb::connect(None)?);
+        }
+        let result = {
+            let (conn, screen_num) = connection.as_ref().unwrap();
+            query(conn, conn.setup().roots[*screen_num].root)
+        };
+        if result.is_err() {
+            *connection = None;
+        }
+        result
+    }
+
+    fn query(conn: &RustConnection, root: Window) -> Result<Option<SourceApp>, Box<dyn Error>> {
+        let clipboard = intern_atom(conn, "CLIPBOARD")?;
+        let owner = conn.get_selection_owner(clipboard)?.reply()?.owner;
+        if let Some(source) = window_source(conn, root, owner)? {
+            return Ok(Some(source));
+        }
+
+        // selection owner 经常是没有 WM_CLASS 的隐藏窗口, 此时用焦点窗口代替
+        let active_window = intern_atom(conn, "_NET_ACTIVE_WINDOW")?;
+        let active = conn
+            .get_property(false, root, active_window, AtomEnum::WINDOW, 0, 1)?
+            .reply()?
+            .value32()
+            .and_then(|mut value| value.next())
+            .unwrap_or(NONE);
+        window_source(conn, root, active)
+    }
+
+    // 从 window 向上查找第一个带 WM_CLASS 的窗口
+    fn window_source(
+        conn: &RustConnection,
+        root: Window,
+        mut window: Window,
+    ) -> Result<Option<SourceApp>, Box<dyn Error>> {
+        while window != NONE && window != root {
+            let class = conn
+                .get_property(false, window, AtomEnum::WM_CLASS, AtomEnum::STRING, 0, 1024)?
+                .reply()?
+                .value;
+            if let Some((instance, app)) = parse_wm_class(&class) {
+                return Ok(Some(SourceApp {
+                    app: Some(app),
+                    title: window_title(conn, window)?,
+                    instance: Some(instance),
+                }));
+            }
+            window = conn.query_tree(window)?.reply()?.parent;
+        }
+        Ok(None)
+    }
+
+    // 优先取 UTF-8 的 _NET_WM_NAME, 没有时取 WM_NAME
+    fn window_title(
+        conn: &RustConnection,
+        window: Window,
+    ) -> Result<Option<String>, Box<dyn Error>> {
+        let net_wm_name = intern_atom(conn, "_NET_WM_NAME")?;
+        let utf8_string = intern_atom(conn, "UTF8_STRING")?;
+        for (property, r#type) in [
+            (net_wm_name, utf8_string),
+            (AtomEnum::WM_NAME.into(), AtomEnum::ANY.into()),
+        ] {
+            let value = conn
+                .get_property(false, window, property, r#type, 0, 1024)?
+                .reply()?
+                .value;
+            let title = String::from_utf8_lossy(&value).trim().to_string();
+            if !title.is_empty() {
+                return Ok(Some(title));
+            }
+        }
+        Ok(None)
+    }
+
+    fn intern_atom(conn: &RustConnection, name: &str) -> Result<Atom, Box<dyn Error>> {
+        Ok(conn.intern_atom(false, name.as_bytes())?.reply()?.atom)
+    }
+
+    // WM_CLASS 为 "instance\0Class\0", 返回 (instance, Class), 缺失 Class 时两者相同
+    fn parse_wm_class(value: &[u8]) -> Option<(String, String)> {
+        let mut parts = value
+            .split(|&b| b == 0)
+            .map(|part| String::from_utf8_lossy(part).trim().to_string())
+            .filter(|part| !part.is_empty());
+        let instance = parts.next()?;
+        let class = parts.next().unwrap_or_else(|| instance.clone());
+        Some((instance, class))
+    }
+}
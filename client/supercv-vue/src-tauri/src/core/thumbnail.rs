@@ -0,0 +1,146 @@
+use crate::core::blob_store::BLOB_STORE;
+use crate::utils::config::CONFIG;
+use image::codecs::jpeg::JpegEncoder;
+use image::{DynamicImage, GenericImageView, ImageError, Rgb, RgbImage};
+use lazy_static::lazy_static;
+use log::{debug, error};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+lazy_static! {
+    // 正在后台生成缩略图的 hash
+    static ref PENDING: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
+}
+
+// 缩略图与原图共用 hash, 保存在 blob store 中
+pub const THUMB_BLOB_EXT: &str = "thumb.jpg";
+const JPEG_QUALITY: u8 = 80;
+
+fn thumbnail_size() -> u32 {
+    CONFIG
+        .read()
+        .unwrap()
+        .user_config
+        .preview_config
+        .thumbnail_size
+}
+
+// 返回与当前配置尺寸一致的缩略图, 只读取图片头, 不生成
+pub fn get_current(hash: &str, source: &str) -> Option<PathBuf> {
+    BLOB_STORE
+        .lookup(hash, THUMB_BLOB_EXT)
+        .filter(|path| is_current(path, source, thumbnail_size()))
+}
+
+// 返回可用的缩略图, 不存在或尺寸与配置不一致时重新生成
+pub fn get_or_create(hash: &str, source: &str) -> io::Result<PathBuf> {
+    let size = thumbnail_size();
+    if let Some(path) = BLOB_STORE.lookup(hash, THUMB_BLOB_EXT) {
+        if is_current(&path, source, size) {
+            return Ok(path);
+        }
+        debug!("thumbnail {} outdated, regenerating", hash);
+        BLOB_STORE.remove(&path)?;
+    }
+    create(hash, source, size)
+}
+
+// 在阻塞线程池中生成, 同一张图片同时只生成一次; 需要在 tokio 运行时中调用
+pub fn create_in_background(hash: String, source: String) {
+    if !PENDING.lock().unwrap().insert(hash.clone()) {
+        return;
+    }
+    tokio::task::spawn_blocking(move || {
+        if let Err(e) = get_or_create(&hash, &source) {
+            error!("Failed to create thumbnail for {}: {}", source, e);
+        }
+        PENDING.lock().unwrap().remove(&hash);
+    });
+}
+
+pub fn create(hash: &str, source: &str, size: u32) -> io::Result<PathBuf> {
+    let img = image::open(source).map_err(to_io_error)?;
+    let thumb = resize(&img, size);
+    BLOB_STORE.put_with(hash, THUMB_BLOB_EXT, |tmp| {
+        let writer = BufWriter::new(File::create(tmp)?);
+        JpegEncoder::new_with_quality(writer, JPEG_QUALITY)
+            .encode_image(&thumb)
+            .map_err(to_io_error)
+    })
+}
+
+// 删除记录时一并删除缩略图, 返回释放的字节数
+pub fn remove(hash: &str) -> u64 {
+    match BLOB_STORE.lookup(hash, THUMB_BLOB_EXT) {
+        Some(path) => BLOB_STORE.remove(&path).unwrap_or_else(|e| {
+            error!("Failed to remove thumbnail {}: {}", path.display(), e);
+            0
+        }),
+        None => 0,
+    }
+}
+
+// 等比缩放到最长边不超过 size, 不放大; JPEG 不支持透明, 透明部分填充白色
+fn resize(img: &DynamicImage, size: u32) -> RgbImage {
+    let (w, h) = img.dimensions();
+    let img = if w.max(h) > size {
+        img.thumbnail(size, size)
+    } else {
+        img.clone()
+    };
+    let rgba = img.to_rgba8();
+    RgbImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+        let [r, g, b, a] = rgba.get_pixel(x, y).0;
+        let blend = |c: u8| ((c as u32 * a as u32 + 255 * (255 - a as u32)) / 255) as u8;
+        Rgb([blend(r), blend(g), blend(b)])
+    })
+}
+
+fn target_side(source: (u32, u32), size: u32) -> u32 {
+    source.0.max(source.1).min(size)
+}
+
+fn is_current(thumb: &Path, source: &str, size: u32) -> bool {
+    match (
+        image::image_dimensions(thumb),
+        image::image_dimensions(source),
+    ) {
+        (Ok((w, h)), Ok(source)) => w.max(h) == target_side(source, size),
+        // 原图已不存在时继续使用旧缩略图
+        (Ok(_), Err(_)) => true,
+        _ => false,
+    }
+}
+
+fn to_io_error(e: ImageError) -> io::Error {
+    match e {
+        ImageError::IoError(e) => e,
+        e => io::Error::new(io::ErrorKind::InvalidData, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgba, RgbaImage};
+
+    #[test]
+    fn test_resize() {
+        let img = DynamicImage::ImageRgba8(RgbaImage::from_pixel(3840, 2160, Rgba([0, 0, 0, 0])));
+        let thumb = resize(&img, 256);
+        assert_eq!(thumb.dimensions(), (256, 144));
+        assert_eq!(thumb.get_pixel(0, 0), &Rgb([255, 255, 255]));
+
+        let small = DynamicImage::ImageRgba8(RgbaImage::new(100, 50));
+        assert_eq!(resize(&small, 256).dimensions(), (100, 50));
+    }
+
+    #[test]
+    fn test_target_side() {
+        assert_eq!(target_side((3840, 2160), 256), 256);
+        assert_eq!(target_side((100, 50), 256), 100);
+    }
+}
@@ -0,0 +1,301 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::de::IgnoredAny;
+use serde::{Deserialize, Serialize};
+
+// 粘贴前对文本的转换, 按顺序依次执行
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Transform {
+    Trim,
+    Lowercase,
+    Uppercase,
+    TitleCase,
+    JoinLines,  // 去掉空行, 各行以空格连接
+    JsonPretty, // 保持键的顺序, 两个空格缩进
+    JsonMinify,
+    UrlEncode,
+    UrlDecode,
+    Base64Encode,
+    Base64Decode,
+    ShellEscape,     // POSIX shell 单引号转义
+    JsonEscape,      // 转义为 JSON 字符串的内容, 不带两侧引号
+    StripFormatting, // 去掉零宽字符和行尾空白, 统一空格和换行
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct TransformError {
+    pub transform: Transform,
+    pub message: String,
+}
+
+impl std::fmt::Display for TransformError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?} failed: {}", self.transform, self.message)
+    }
+}
+
+impl std::error::Error for TransformError {}
+
+pub fn apply(text: &str, transforms: &[Transform]) -> Result<String, TransformError> {
+    transforms
+        .iter()
+        .try_fold(text.to_string(), |text, &transform| {
+            transform
+                .apply(&text)
+                .map_err(|message| TransformError { transform, message })
+        })
+}
+
+impl Transform {
+    fn apply(self, text: &str) -> Result<String, String> {
+        let result = match self {
+            Transform::Trim => text.trim().to_string(),
+            Transform::Lowercase => text.to_lowercase(),
+            Transform::Uppercase => text.to_uppercase(),
+            Transform::TitleCase => title_case(text),
+            Transform::JoinLines => text
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .collect::<Vec<_>>()
+                .join(" "),
+            Transform::JsonPretty => reformat_json(text, true)?,
+            Transform::JsonMinify => reformat_json(text, false)?,
+            Transform::UrlEncode => urlencoding::encode(text).into_owned(),
+            Transform::UrlDecode => urlencoding::decode(text)
+                .map_err(|e| e.to_string())?
+                .into_owned(),
+            Transform::Base64Encode => BASE64.encode(text),
+            Transform::Base64Decode => {
+                // 忽略复制时带上的换行和空白
+                let compact: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+                let bytes = BASE64.decode(compact).map_err(|e| e.to_string())?;
+                String::from_utf8(bytes).map_err(|_| "decoded data is not UTF-8".to_string())?
+            }
+            Transform::ShellEscape => shell_escape(text),
+            Transform::JsonEscape => {
+                let quoted = serde_json::to_string(text).map_err(|e| e.to_string())?;
+                quoted[1..quoted.len() - 1].to_string()
+            }
+            Transform::StripFormatting => strip_formatting(text),
+        };
+        Ok(result)
+    }
+}
+
+// 每个单词首字母大写, 其余小写
+fn title_case(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut word_start = true;
+    for c in text.chars() {
+        if word_start {
+            result.extend(c.to_uppercase());
+        } else {
+            result.extend(c.to_lowercase());
+        }
+        word_start = c.is_whitespace() || c == '-' || c == '_';
+    }
+    result
+}
+
+fn shell_escape(text: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "_@%+=:,./-".contains(c);
+    if !text.is_empty() && text.chars().all(safe) {
+        return text.to_string();
+    }
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+fn strip_formatting(text: &str) -> String {
+    text.replace("\r\n", "\n")
+        .replace('\r', "\n")
+        .chars()
+        .filter(|c| !matches!(c, '\u{200B}'..='\u{200D}' | '\u{2060}' | '\u{FEFF}'))
+        .map(|c| match c {
+            '\u{00A0}' | '\u{2000}'..='\u{200A}' | '\u{202F}' | '\u{3000}' => ' ',
+            c => c,
+        })
+        .collect::<String>()
+        .lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// 逐字符重新排版, 不经过 serde_json::Value, 保留键的顺序和数字的原始写法
+fn reformat_json(text: &str, pretty: bool) -> Result<String, String> {
+    serde_json::from_str::<IgnoredAny>(text).map_err(|e| e.to_string())?;
+
+    let mut out = String::with_capacity(text.len());
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut chars = text.trim().chars().peekable();
+    let newline = |out: &mut String, depth: usize| {
+        if pretty {
+            out.push('\n');
+            out.push_str(&"  ".repeat(depth));
+        }
+    };
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => {
+                in_string = true;
+                out.push(c);
+            }
+            '{' | '[' => {
+                out.push(c);
+                // 空对象和空数组保持在一行
+                while chars.peek().is_some_and(|c| c.is_whitespace()) {
+                    chars.next();
+                }
+                if matches!(chars.peek(), Some('}') | Some(']')) {
+                    out.push(chars.next().unwrap());
+                } else {
+                    depth += 1;
+                    newline(&mut out, depth);
+                }
+            }
+            '}' | ']' => {
+                depth -= 1;
+                newline(&mut out, depth);
+                out.push(c);
+            }
+            ',' => {
+                out.push(c);
+                newline(&mut out, depth);
+            }
+            ':' => out.push_str(if pretty { ": " } else { ":" }),
+            c if c.is_whitespace() => {}
+            c => out.push(c),
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(transform: Transform, text: &str) -> String {
+        apply(text, &[transform]).unwrap()
+    }
+
+    #[test]
+    fn test_trim() {
+        assert_eq!(run(Transform::Trim, "  a b \n"), "a b");
+    }
+
+    #[test]
+    fn test_case() {
+        assert_eq!(run(Transform::Lowercase, "Hello WÖRLD"), "hello wörld");
+        assert_eq!(run(Transform::Uppercase, "Hello wörld"), "HELLO WÖRLD");
+        assert_eq!(
+            run(Transform::TitleCase, "hello wORLD\nsuper-cv"),
+            "Hello World\nSuper-Cv"
+        );
+    }
+
+    #[test]
+    fn test_join_lines() {
+        assert_eq!(run(Transform::JoinLines, "  a\n\n b \r\nc"), "a b c");
+    }
+
+    #[test]
+    fn test_json_pretty() {
+        let text = r#"{"b":1,"a":[1, 2,{}],"s":"x, {y}: \"z\"","e":[]}"#;
+        let expected = "{\n  \"b\": 1,\n  \"a\": [\n    1,\n    2,\n    {}\n  ],\n  \"s\": \"x, {y}: \\\"z\\\"\",\n  \"e\": []\n}";
+        assert_eq!(run(Transform::JsonPretty, text), expected);
+    }
+
+    #[test]
+    fn test_json_minify() {
+        let text = "{\n  \"b\": 1.50,\n  \"a\": [ 1, 2 ],\n  \"s\": \"a b\"\n}";
+        assert_eq!(
+            run(Transform::JsonMinify, text),
+            r#"{"b":1.50,"a":[1,2],"s":"a b"}"#
+        );
+        let err = apply("{not json", &[Transform::JsonMinify]).unwrap_err();
+        assert_eq!(err.transform, Transform::JsonMinify);
+    }
+
+    #[test]
+    fn test_url() {
+        assert_eq!(
+            run(Transform::UrlEncode, "a b&c=中"),
+            "a%20b%26c%3D%E4%B8%AD"
+        );
+        assert_eq!(
+            run(Transform::UrlDecode, "a%20b%26c%3D%E4%B8%AD"),
+            "a b&c=中"
+        );
+        assert!(apply("%FF", &[Transform::UrlDecode]).is_err());
+    }
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(
+            run(Transform::Base64Encode, "super-cv 中"),
+            "c3VwZXItY3Yg5Lit"
+        );
+        assert_eq!(
+            run(Transform::Base64Decode, "c3VwZXIt\nY3Yg5Lit"),
+            "super-cv 中"
+        );
+        assert!(apply("!!", &[Transform::Base64Decode]).is_err());
+        assert!(apply("/w==", &[Transform::Base64Decode]).is_err());
+    }
+
+    #[test]
+    fn test_shell_escape() {
+        assert_eq!(run(Transform::ShellEscape, "/tmp/a.txt"), "/tmp/a.txt");
+        assert_eq!(
+            run(Transform::ShellEscape, "it's a $HOME"),
+            r"'it'\''s a $HOME'"
+        );
+        assert_eq!(run(Transform::ShellEscape, ""), "''");
+    }
+
+    #[test]
+    fn test_json_escape() {
+        assert_eq!(
+            run(Transform::JsonEscape, "say \"hi\"\n\tC:\\"),
+            r#"say \"hi\"\n\tC:\\"#
+        );
+    }
+
+    #[test]
+    fn test_strip_formatting() {
+        assert_eq!(
+            run(
+                Transform::StripFormatting,
+                "a\u{00A0}b\u{200B}c  \r\nd\u{3000}e\t"
+            ),
+            "a bc\nd e"
+        );
+    }
+
+    #[test]
+    fn test_chain() {
+        let transforms = [
+            Transform::Trim,
+            Transform::Uppercase,
+            Transform::Base64Encode,
+        ];
+        assert_eq!(apply("  ab ", &transforms).unwrap(), "QUI=");
+        let transforms: Vec<Transform> =
+            serde_json::from_str(r#"["join_lines", "shell_escape"]"#).unwrap();
+        assert_eq!(apply("a\nb", &transforms).unwrap(), "'a b'");
+    }
+}
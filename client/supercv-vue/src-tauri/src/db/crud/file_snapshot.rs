@@ -0,0 +1,76 @@
+use crate::core::snapshot::Snapshot;
+use crate::db::entities::file_snapshot;
+use crate::db::entities::prelude::FileSnapshot;
+use sea_orm::ActiveValue::Set;
+use sea_orm::*;
+
+// 保存条目的文件副本, 同一文件内容变化时返回不再引用的旧 blob
+pub async fn add_snapshots(
+    db: &DatabaseConnection,
+    clipboard_id: i32,
+    snapshots: Vec<Snapshot>,
+) -> Result<Vec<String>, DbErr> {
+    let mut replaced = Vec::new();
+    for snapshot in snapshots {
+        let existing = FileSnapshot::find()
+            .filter(file_snapshot::Column::ClipboardId.eq(clipboard_id))
+            .filter(file_snapshot::Column::OriginalPath.eq(snapshot.original_path.as_str()))
+            .one(db)
+            .await?;
+        match existing {
+            Some(existing) if existing.blob_path == snapshot.blob_path => {}
+            Some(existing) => {
+                let old_blob = existing.blob_path.clone();
+                let mut existing: file_snapshot::ActiveModel = existing.into();
+                existing.blob_path = Set(snapshot.blob_path);
+                existing.size = Set(snapshot.size);
+                existing.update(db).await?;
+                replaced.push(old_blob);
+            }
+            None => {
+                file_snapshot::ActiveModel {
+                    clipboard_id: Set(clipboard_id),
+                    original_path: Set(snapshot.original_path),
+                    blob_path: Set(snapshot.blob_path),
+                    size: Set(snapshot.size),
+                    ..Default::default()
+                }
+                .insert(db)
+                .await?;
+            }
+        }
+    }
+    Ok(replaced)
+}
+
+pub async fn get_snapshots(
+    db: &DatabaseConnection,
+    clipboard_ids: Vec<i32>,
+) -> Result<Vec<file_snapshot::Model>, DbErr> {
+    FileSnapshot::find()
+        .filter(file_snapshot::Column::ClipboardId.is_in(clipboard_ids))
+        .all(db)
+        .await
+}
+
+// 这些记录引用的 blob 路径
+pub async fn get_blob_paths(
+    db: &DatabaseConnection,
+    clipboard_ids: Vec<i32>,
+) -> Result<Vec<String>, DbErr> {
+    FileSnapshot::find()
+        .select_only()
+        .column(file_snapshot::Column::BlobPath)
+        .distinct()
+        .filter(file_snapshot::Column::ClipboardId.is_in(clipboard_ids))
+        .into_tuple()
+        .all(db)
+        .await
+}
+
+pub async fn count_path_refs(db: &DatabaseConnection, path: &str) -> Result<u64, DbErr> {
+    FileSnapshot::find()
+        .filter(file_snapshot::Column::BlobPath.eq(path))
+        .count(db)
+        .await
+}
@@ -1,16 +1,30 @@
-use crate::core::pasteboard::PasteboardContent;
+use crate::core::blob_store::BLOB_STORE;
+use crate::core::pasteboard::{ContentType, PasteboardContent};
+use crate::core::thumbnail;
+use crate::core::query::{SearchQuery, SearchTerm, SizeCmp};
+use crate::db::crud::{file_snapshot, representation, tag};
 use crate::db::entities::host_clipboard::{self, Entity as ClipboardEntries};
 use crate::utils::config::CONFIG;
-use sea_orm::sea_query::{Expr, OnConflict};
+use sea_orm::sea_query::{Alias, Expr, OnConflict, Query, SimpleExpr};
 use sea_orm::ActiveValue::Set;
 use sea_orm::*;
 use crate::db::entities::prelude::HostClipboard;
+use log::error;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::Path;
 
 pub async fn add_clipboard_entry(
     db: &DatabaseConnection,
     item: PasteboardContent,
 ) -> Result<host_clipboard::Model, DbErr> {
     let timestamp = item.date_time.timestamp();
+    let representations = if item.representations.is_empty() {
+        item.fallback_representations()
+    } else {
+        item.representations
+    };
+    let source = item.source.unwrap_or_default();
 
     // 使用 Sea-ORM 的查询构建器进行插入或更新操作
     let _ = ClipboardEntries::insert(host_clipboard::ActiveModel {
@@ -19,31 +33,57 @@ pub async fn add_clipboard_entry(
         content: Set(item.text_content),
         timestamp: Set(timestamp),
         hash: Set(item.hash.clone()),
+        size: Set(item.size),
+        source_app: Set(source.app),
+        source_title: Set(source.title),
+        html: Set(item.html),
+        rtf: Set(item.rtf),
+        subtype: Set(item.subtype.map(|subtype| subtype.to_i32())),
+        version_key: Set(item.version_key),
+        summary: Set(item.summary),
         ..Default::default()
     })
     .on_conflict(
+        // 重复复制时记录最近一次的来源; 目录的 hash 不包含其中文件的改动,
+        // 需要更新统计信息和大小
         OnConflict::column(host_clipboard::Column::Hash)
-            .update_column(host_clipboard::Column::Timestamp)
+            .update_columns([
+                host_clipboard::Column::Timestamp,
+                host_clipboard::Column::SourceApp,
+                host_clipboard::Column::SourceTitle,
+                host_clipboard::Column::Content,
+                host_clipboard::Column::Size,
+                host_clipboard::Column::Summary,
+            ])
             .to_owned(),
     )
     .exec(db)
     .await?;
 
     // 查询刚插入或更新的记录
-    ClipboardEntries::find()
+    let entry = ClipboardEntries::find()
         .filter(host_clipboard::Column::Hash.eq(item.hash))
         .one(db)
         .await?
         .ok_or(DbErr::Custom(
             "Failed to retrieve inserted or updated entry".to_string(),
-        ))
+        ))?;
+
+    // 重复复制时格式内容可能不同, 旧的 blob 不再被引用时删除
+    let replaced = representation::add_representations(db, entry.id, representations).await?;
+    for blob_path in replaced {
+        release_blob(db, &blob_path).await?;
+    }
+    Ok(entry)
 }
 
 pub async fn get_clipboards_by_type_list(
     db: &DatabaseConnection,
-    text: Option<&str>,
     num: Option<u64>,
     type_list: Option<Vec<i32>>,
+    tag_list: Option<Vec<i32>>,
+    subtype_list: Option<Vec<i32>>,
+    group_versions: bool,
 ) -> Result<Vec<host_clipboard::Model>, DbErr> {
     let (text_ts, img_ts, file_ts) = {
         let config = CONFIG.read().unwrap(); // 获取读锁
@@ -51,33 +91,19 @@ pub async fn get_clipboards_by_type_list(
         (text_ts, img_ts, file_ts) // 将值返回给外部变量
     };
 
+    // 同一文件的多个版本只返回最新的一条, 只和同样满足过滤条件的版本比较
+    let latest_version = group_versions.then(|| {
+        latest_version_condition(
+            type_list.as_deref(),
+            tag_list.as_deref(),
+            subtype_list.as_deref(),
+        )
+    });
+
     let mut query = HostClipboard::find();
 
     // 根据不同的类型指定不同的时间戳
-    query = query.filter(
-        Condition::any()
-            .add(
-                Expr::col(host_clipboard::Column::Type)
-                    .eq(0)
-                    .and(host_clipboard::Column::Timestamp.gt(text_ts)),
-            )
-            .add(
-                Expr::col(host_clipboard::Column::Type)
-                    .eq(1)
-                    .and(host_clipboard::Column::Timestamp.gt(img_ts)),
-            )
-            .add(
-                Expr::col(host_clipboard::Column::Type)
-                    .eq(2)
-                    .and(host_clipboard::Column::Timestamp.gt(file_ts)),
-            ),
-    );
-
-    if let Some(text) = text {
-        query = query.filter(
-            Expr::cust("LOWER(content)").like(format!("%{}%", text)), // 直接使用原始文本进行模糊匹配
-        );
-    }
+    query = query.filter(unexpired_condition(text_ts, img_ts, file_ts));
 
     if let Some(num) = num {
         query = query.limit(num);
@@ -88,12 +114,460 @@ pub async fn get_clipboards_by_type_list(
         query = query.filter(host_clipboard::Column::Type.is_in(type_list));
     }
 
+    // 只保留打了任一指定标签的记录
+    if let Some(tag_list) = tag_list {
+        query = query.filter(tag::tagged_condition(tag::clipboard_ids_by_tag_ids(tag_list)));
+    }
+
+    // 只保留指定细分类型的文本记录
+    if let Some(subtype_list) = subtype_list {
+        query = query.filter(host_clipboard::Column::Subtype.is_in(subtype_list));
+    }
+
+    if let Some(latest_version) = latest_version {
+        query = query.filter(latest_version);
+    }
+
     // 按时间戳降序排序并限制结果数量
     query = query.order_by_desc(host_clipboard::Column::Timestamp);
 
     query.all(db).await
 }
 
+#[derive(Serialize, Debug, Clone)]
+pub struct SearchHit {
+    #[serde(flatten)]
+    pub entry: host_clipboard::Model,
+    // 命中位置 [start, end), 以 UTF-16 计数, 与前端字符串下标一致
+    pub highlights: Vec<(usize, usize)>,
+}
+
+struct SearchRow {
+    entry: host_clipboard::Model,
+    highlighted: Option<String>,
+}
+
+impl FromQueryResult for SearchRow {
+    fn from_query_result(res: &QueryResult, pre: &str) -> Result<Self, DbErr> {
+        Ok(Self {
+            entry: host_clipboard::Model::from_query_result(res, pre)?,
+            highlighted: res.try_get(pre, "highlighted").ok(),
+        })
+    }
+}
+
+// highlight() 插入的命中标记
+const HIGHLIGHT_OPEN: char = '\u{2}';
+const HIGHLIGHT_CLOSE: char = '\u{3}';
+
+// 全文搜索: 英文等按 FTS5 分词并以 BM25 排序, 中日韩文本按子串匹配
+pub async fn search_clipboards(
+    db: &DatabaseConnection,
+    search: &SearchQuery,
+    num: Option<u64>,
+    type_list: Option<Vec<i32>>,
+    tag_list: Option<Vec<i32>>,
+) -> Result<Vec<SearchHit>, DbErr> {
+    let (text_ts, img_ts, file_ts) = CONFIG.read().unwrap().get_expired_ts();
+    let (cjk_terms, fts_terms): (Vec<&SearchTerm>, Vec<&SearchTerm>) = search
+        .terms
+        .iter()
+        .filter(|term| !term.negated)
+        .partition(|term| term.text.chars().any(is_cjk));
+
+    let mut query = HostClipboard::find()
+        .filter(unexpired_condition(text_ts, img_ts, file_ts))
+        .filter(search_condition(search));
+
+    if let Some(type_list) = type_list {
+        query = query.filter(host_clipboard::Column::Type.is_in(type_list));
+    }
+
+    if let Some(tag_list) = tag_list {
+        query = query.filter(tag::tagged_condition(tag::clipboard_ids_by_tag_ids(tag_list)));
+    }
+
+    for term in cjk_terms.iter() {
+        query = query.filter(content_like(&term.text));
+    }
+
+    if !fts_terms.is_empty() {
+        let fts_query = fts_terms
+            .iter()
+            .map(|term| to_fts(term))
+            .collect::<Vec<_>>()
+            .join(" ");
+        QueryTrait::query(&mut query).join(
+            JoinType::InnerJoin,
+            Alias::new("host_clipboard_fts"),
+            Expr::cust("host_clipboard_fts.rowid = host_clipboard.id"),
+        );
+        query = query
+            .column_as(
+                Expr::cust("highlight(host_clipboard_fts, 0, char(2), char(3))"),
+                "highlighted",
+            )
+            .filter(Expr::cust_with_values("host_clipboard_fts MATCH ?", [fts_query]))
+            .order_by(Expr::cust("bm25(host_clipboard_fts)"), Order::Asc);
+    }
+
+    query = query.order_by_desc(host_clipboard::Column::Timestamp);
+    if let Some(num) = num {
+        query = query.limit(num);
+    }
+
+    let rows = query.into_model::<SearchRow>().all(db).await?;
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let mut highlights = row
+                .highlighted
+                .as_deref()
+                .map(highlight_offsets)
+                .unwrap_or_default();
+            for term in cjk_terms.iter() {
+                highlights.extend(substring_offsets(&row.entry.content, &term.text));
+            }
+            highlights.sort();
+            SearchHit {
+                entry: row.entry,
+                highlights,
+            }
+        })
+        .collect())
+}
+
+// 查询语法中除关键词以外的过滤条件
+fn search_condition(search: &SearchQuery) -> Condition {
+    let mut condition = Condition::all();
+
+    if !search.types.is_empty() {
+        condition = condition.add(host_clipboard::Column::Type.is_in(search.types.clone()));
+    }
+    if !search.exclude_types.is_empty() {
+        condition =
+            condition.add(host_clipboard::Column::Type.is_not_in(search.exclude_types.clone()));
+    }
+    if let Some(after) = search.after {
+        condition = condition.add(host_clipboard::Column::Timestamp.gte(after));
+    }
+    if let Some(before) = search.before {
+        condition = condition.add(host_clipboard::Column::Timestamp.lt(before));
+    }
+    for (cmp, size) in search.sizes.iter() {
+        let column = host_clipboard::Column::Size;
+        condition = condition.add(match cmp {
+            SizeCmp::Gt => column.gt(*size),
+            SizeCmp::Ge => column.gte(*size),
+            SizeCmp::Lt => column.lt(*size),
+            SizeCmp::Le => column.lte(*size),
+            SizeCmp::Eq => column.eq(*size),
+        });
+    }
+    for path in search.paths.iter() {
+        condition = condition.add(path_like(path));
+    }
+    for path in search.exclude_paths.iter() {
+        condition = condition.add(path_like(path).not());
+    }
+    for name in search.tags.iter() {
+        condition = condition.add(tag::tagged_condition(tag::clipboard_ids_by_tag_name(name)));
+    }
+    for name in search.exclude_tags.iter() {
+        condition =
+            condition.add(tag::tagged_condition(tag::clipboard_ids_by_tag_name(name)).not());
+    }
+    for app in search.apps.iter() {
+        condition = condition.add(source_like(app));
+    }
+    // 来源未知的记录不会被排除
+    for app in search.exclude_apps.iter() {
+        condition = condition.add(source_like(app).not());
+    }
+    for term in search.terms.iter().filter(|term| term.negated) {
+        condition = condition.add(content_like(&term.text).not());
+    }
+    condition
+}
+
+fn content_like(text: &str) -> SimpleExpr {
+    Expr::cust_with_values(
+        "host_clipboard.content LIKE ? ESCAPE '\\'",
+        [format!("%{}%", escape_like(text))],
+    )
+}
+
+// 多文件条目的 path 为 JSON 数组, 其中的反斜杠和引号已转义, 两种写法都要匹配
+fn path_like(path: &str) -> SimpleExpr {
+    let like = |pattern: &str| {
+        Expr::cust_with_values(
+            "host_clipboard.path LIKE ? ESCAPE '\\'",
+            [format!("%{}%", escape_like(pattern))],
+        )
+    };
+    let json = serde_json::to_string(path).unwrap();
+    like(path).or(like(&json[1..json.len() - 1]))
+}
+
+// 来源应用(WM_CLASS)或窗口标题包含 app
+fn source_like(app: &str) -> SimpleExpr {
+    // ESCAPE '\' 必须放在表达式末尾, 否则之后的占位符会被当作字符串的一部分
+    let like = |column: &str| {
+        Expr::cust_with_values(
+            format!("IFNULL(host_clipboard.{}, '') LIKE ? ESCAPE '\\'", column),
+            [format!("%{}%", escape_like(app))],
+        )
+    };
+    like("source_app").or(like("source_title"))
+}
+
+// 单词按前缀匹配, 引号内的短语按整体匹配
+fn to_fts(term: &SearchTerm) -> String {
+    let quoted = format!("\"{}\"", term.text.replace('"', "\"\""));
+    if term.phrase {
+        quoted
+    } else {
+        format!("{}*", quoted)
+    }
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{2E80}'..='\u{9FFF}' | '\u{AC00}'..='\u{D7AF}' | '\u{F900}'..='\u{FAFF}' | '\u{FF00}'..='\u{FFEF}')
+}
+
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+// 解析 highlight() 的结果, 得到命中位置
+fn highlight_offsets(highlighted: &str) -> Vec<(usize, usize)> {
+    let mut offsets = Vec::new();
+    let mut pos = 0;
+    let mut start = None;
+    for c in highlighted.chars() {
+        match c {
+            HIGHLIGHT_OPEN => start = Some(pos),
+            HIGHLIGHT_CLOSE => {
+                if let Some(start) = start.take() {
+                    offsets.push((start, pos));
+                }
+            }
+            _ => pos += c.len_utf16(),
+        }
+    }
+    offsets
+}
+
+// 不区分大小写地查找 term 在 content 中的所有位置
+fn substring_offsets(content: &str, term: &str) -> Vec<(usize, usize)> {
+    let term: Vec<char> = term.chars().flat_map(char::to_lowercase).collect();
+    let content: Vec<char> = content.chars().collect();
+    let term_len = term.len();
+    let mut offsets = Vec::new();
+    if term_len == 0 || term_len > content.len() {
+        return offsets;
+    }
+    let mut pos = 0;
+    for i in 0..content.len() {
+        let window = &content[i..(i + term_len).min(content.len())];
+        if window.len() == term_len
+            && window
+                .iter()
+                .zip(term.iter())
+                .all(|(a, b)| a.to_lowercase().eq(std::iter::once(*b)))
+        {
+            let len: usize = window.iter().map(|c| c.len_utf16()).sum();
+            offsets.push((pos, pos + len));
+        }
+        pos += content[i].len_utf16();
+    }
+    offsets
+}
+
+// 未过期的记录: 按类型比较各自的保留时间, 置顶的记录永不过期
+fn unexpired_condition(text_ts: i64, img_ts: i64, file_ts: i64) -> Condition {
+    Condition::any()
+        .add(host_clipboard::Column::Pinned.eq(true))
+        .add(
+            // 富文本与纯文本使用相同的保留时间
+            Expr::col(host_clipboard::Column::Type)
+                .is_in([0, 3])
+                .and(host_clipboard::Column::Timestamp.gt(text_ts)),
+        )
+        .add(
+            Expr::col(host_clipboard::Column::Type)
+                .eq(1)
+                .and(host_clipboard::Column::Timestamp.gt(img_ts)),
+        )
+        .add(
+            // 目录与文件使用相同的保留时间
+            Expr::col(host_clipboard::Column::Type)
+                .is_in([2, 4])
+                .and(host_clipboard::Column::Timestamp.gt(file_ts)),
+        )
+}
+
+// 没有更新版本的记录, 更新的版本也要满足相同的过滤条件; version_key 为 NULL 的记录总是满足
+fn latest_version_condition(
+    type_list: Option<&[i32]>,
+    tag_list: Option<&[i32]>,
+    subtype_list: Option<&[i32]>,
+) -> SimpleExpr {
+    use host_clipboard::Column;
+    let v = Alias::new("v");
+    let newer_col = |column: Column| Expr::col((v.clone(), column));
+    let outer_col = |column: Column| Expr::col((HostClipboard, column));
+
+    let mut newer = Query::select();
+    newer
+        .expr(Expr::val(1))
+        .from_as(HostClipboard, v.clone())
+        .and_where(newer_col(Column::VersionKey).eq(outer_col(Column::VersionKey)))
+        .cond_where(
+            Condition::any()
+                .add(newer_col(Column::Timestamp).gt(outer_col(Column::Timestamp)))
+                .add(
+                    newer_col(Column::Timestamp)
+                        .eq(outer_col(Column::Timestamp))
+                        .and(newer_col(Column::Id).gt(outer_col(Column::Id))),
+                ),
+        );
+    if let Some(type_list) = type_list {
+        newer.and_where(newer_col(Column::Type).is_in(type_list.to_vec()));
+    }
+    if let Some(tag_list) = tag_list {
+        let tagged = tag::clipboard_ids_by_tag_ids(tag_list.to_vec());
+        newer.and_where(newer_col(Column::Id).in_subquery(tagged));
+    }
+    if let Some(subtype_list) = subtype_list {
+        newer.and_where(newer_col(Column::Subtype).is_in(subtype_list.to_vec()));
+    }
+    Expr::exists(newer).not()
+}
+
+// 返回与 id 对应的记录属于同一文件的全部版本, 按时间戳降序
+pub async fn get_clipboard_versions(
+    db: &DatabaseConnection,
+    id: i32,
+) -> Result<Vec<host_clipboard::Model>, DbErr> {
+    let entry = ClipboardEntries::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or(DbErr::RecordNotFound(format!("clipboard {} not found", id)))?;
+    match entry.version_key {
+        Some(version_key) => {
+            HostClipboard::find()
+                .filter(host_clipboard::Column::VersionKey.eq(version_key))
+                .order_by_desc(host_clipboard::Column::Timestamp)
+                .order_by_desc(host_clipboard::Column::Id)
+                .all(db)
+                .await
+        }
+        None => Ok(vec![entry]),
+    }
+}
+
+// 删除超过保留时间的记录, 返回被删除的记录以及它们的格式引用的 blob
+pub async fn delete_expired_entries(
+    db: &DatabaseConnection,
+) -> Result<(Vec<host_clipboard::Model>, Vec<String>), DbErr> {
+    let (text_ts, img_ts, file_ts) = CONFIG.read().unwrap().get_expired_ts();
+
+    let expired = HostClipboard::find()
+        .filter(unexpired_condition(text_ts, img_ts, file_ts).not())
+        .all(db)
+        .await?;
+    if expired.is_empty() {
+        return Ok((expired, Vec::new()));
+    }
+
+    let ids: Vec<i32> = expired.iter().map(|entry| entry.id).collect();
+    // 格式和文件副本由外键级联删除, 删除前先取出引用的 blob
+    let mut blob_paths = representation::get_blob_paths(db, ids.clone()).await?;
+    blob_paths.extend(file_snapshot::get_blob_paths(db, ids.clone()).await?);
+    HostClipboard::delete_many()
+        .filter(host_clipboard::Column::Id.is_in(ids))
+        .exec(db)
+        .await?;
+    Ok((expired, blob_paths))
+}
+
+// 置顶/取消置顶, 新置顶的记录排在最后
+pub async fn set_clipboard_pinned(
+    db: &DatabaseConnection,
+    id: i32,
+    pinned: bool,
+) -> Result<host_clipboard::Model, DbErr> {
+    let entry = ClipboardEntries::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or(DbErr::RecordNotFound(format!("clipboard {} not found", id)))?;
+
+    let pin_order = if pinned {
+        let last_order: Option<Option<i32>> = HostClipboard::find()
+            .select_only()
+            .column_as(host_clipboard::Column::PinOrder.max(), "pin_order")
+            .filter(host_clipboard::Column::Pinned.eq(true))
+            .into_tuple()
+            .one(db)
+            .await?;
+        Some(last_order.flatten().map_or(0, |order| order + 1))
+    } else {
+        None
+    };
+
+    let mut entry: host_clipboard::ActiveModel = entry.into();
+    entry.pinned = Set(pinned);
+    entry.pin_order = Set(pin_order);
+    entry.update(db).await
+}
+
+// 按用户定义的顺序返回所有置顶记录
+pub async fn get_pinned_clipboards(
+    db: &DatabaseConnection,
+) -> Result<Vec<host_clipboard::Model>, DbErr> {
+    HostClipboard::find()
+        .filter(host_clipboard::Column::Pinned.eq(true))
+        .order_by_asc(host_clipboard::Column::PinOrder)
+        .order_by_desc(host_clipboard::Column::Timestamp)
+        .all(db)
+        .await
+}
+
+// 粘贴后把记录移到最前
+pub async fn touch_clipboard_entries(
+    db: &DatabaseConnection,
+    id_list: Vec<i32>,
+    timestamp: i64,
+) -> Result<(), DbErr> {
+    HostClipboard::update_many()
+        .col_expr(host_clipboard::Column::Timestamp, Expr::value(timestamp))
+        .filter(host_clipboard::Column::Id.is_in(id_list))
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+// 按 id_list 的顺序重排置顶记录
+pub async fn reorder_pinned_clipboards(
+    db: &DatabaseConnection,
+    id_list: Vec<i32>,
+) -> Result<(), DbErr> {
+    let txn = db.begin().await?;
+    for (order, id) in id_list.into_iter().enumerate() {
+        HostClipboard::update_many()
+            .col_expr(host_clipboard::Column::PinOrder, Expr::value(order as i32))
+            .filter(host_clipboard::Column::Id.eq(id))
+            .filter(host_clipboard::Column::Pinned.eq(true))
+            .exec(&txn)
+            .await?;
+    }
+    txn.commit().await
+}
+
 pub async fn get_clipboard_entries_by_gt_timestamp(
     db: &DatabaseConnection,
     timestamp: i64,
@@ -140,5 +614,134 @@ pub async fn delete_clipboard_entry(
     db: &DatabaseConnection,
     id: i32,
 ) -> Result<DeleteResult, DbErr> {
-    ClipboardEntries::delete_by_id(id).exec(db).await
+    let entry = ClipboardEntries::find_by_id(id).one(db).await?;
+    let mut blob_paths = representation::get_blob_paths(db, vec![id]).await?;
+    blob_paths.extend(file_snapshot::get_blob_paths(db, vec![id]).await?);
+    let result = ClipboardEntries::delete_by_id(id).exec(db).await?;
+    if let Some(entry) = entry {
+        if entry.r#type == ContentType::Image.to_i32() {
+            thumbnail::remove(&entry.hash);
+        }
+        blob_paths.push(entry.path);
+    }
+    // 截图的 PNG 格式与记录引用同一个 blob
+    blob_paths.sort();
+    blob_paths.dedup();
+    for blob_path in blob_paths {
+        release_blob(db, &blob_path).await?;
+    }
+    Ok(result)
+}
+
+// 统计引用该路径的记录数, 包括记录的格式和文件副本
+pub async fn count_path_refs(db: &DatabaseConnection, path: &str) -> Result<u64, DbErr> {
+    let entry_refs = HostClipboard::find()
+        .filter(host_clipboard::Column::Path.eq(path))
+        .count(db)
+        .await?;
+    Ok(entry_refs
+        + representation::count_path_refs(db, path).await?
+        + file_snapshot::count_path_refs(db, path).await?)
+}
+
+fn is_file_entry(entry: &host_clipboard::Model) -> bool {
+    [
+        ContentType::Image.to_i32(),
+        ContentType::File.to_i32(),
+        ContentType::Directory.to_i32(),
+    ]
+    .contains(&entry.r#type)
+}
+
+// 文件条目中已保存副本的 (记录 id, 原路径)
+pub async fn get_snapshotted_paths<'a, I>(
+    db: &DatabaseConnection,
+    entries: I,
+) -> Result<HashSet<(i32, String)>, DbErr>
+where
+    I: IntoIterator<Item = &'a host_clipboard::Model>,
+{
+    let ids: Vec<i32> = entries
+        .into_iter()
+        .filter(|entry| is_file_entry(entry))
+        .map(|entry| entry.id)
+        .collect();
+    if ids.is_empty() {
+        return Ok(HashSet::new());
+    }
+    Ok(file_snapshot::get_snapshots(db, ids)
+        .await?
+        .into_iter()
+        .map(|snapshot| (snapshot.clipboard_id, snapshot.original_path))
+        .collect())
+}
+
+// 原文件既不存在也没有副本时为 broken; 只访问文件系统, 调用时不需要持有数据库锁.
+// 更新 entries 中的状态, 返回状态有变化的 (记录 id, broken)
+pub fn check_broken_flags<'a, I>(
+    entries: I,
+    snapshotted: &HashSet<(i32, String)>,
+) -> Vec<(i32, bool)>
+where
+    I: IntoIterator<Item = &'a mut host_clipboard::Model>,
+{
+    let mut changes = Vec::new();
+    for entry in entries.into_iter().filter(|entry| is_file_entry(entry)) {
+        let broken = entry
+            .paths()
+            .into_iter()
+            .any(|path| !Path::new(&path).exists() && !snapshotted.contains(&(entry.id, path)));
+        if broken != entry.broken {
+            entry.broken = broken;
+            changes.push((entry.id, broken));
+        }
+    }
+    changes
+}
+
+pub async fn save_broken_flags(
+    db: &DatabaseConnection,
+    changes: Vec<(i32, bool)>,
+) -> Result<(), DbErr> {
+    for broken in [true, false] {
+        let ids: Vec<i32> = changes
+            .iter()
+            .filter(|(_, b)| *b == broken)
+            .map(|(id, _)| *id)
+            .collect();
+        if ids.is_empty() {
+            continue;
+        }
+        HostClipboard::update_many()
+            .col_expr(host_clipboard::Column::Broken, Expr::value(broken))
+            .filter(host_clipboard::Column::Id.is_in(ids))
+            .exec(db)
+            .await?;
+    }
+    Ok(())
+}
+
+// 检查文件条目的原文件, 既不存在也没有副本时标记为 broken, 状态变化时写回数据库
+pub async fn refresh_broken_flags<'a, I>(db: &DatabaseConnection, entries: I) -> Result<(), DbErr>
+where
+    I: IntoIterator<Item = &'a mut host_clipboard::Model>,
+{
+    let mut entries: Vec<&mut host_clipboard::Model> = entries.into_iter().collect();
+    let snapshotted = get_snapshotted_paths(db, entries.iter().map(|entry| &**entry)).await?;
+    let changes = check_broken_flags(entries.iter_mut().map(|entry| &mut **entry), &snapshotted);
+    save_broken_flags(db, changes).await
+}
+
+// 没有记录再引用该 blob 时删除文件, 返回释放的字节数
+pub async fn release_blob(db: &DatabaseConnection, blob_path: &str) -> Result<u64, DbErr> {
+    if !BLOB_STORE.contains(blob_path) || count_path_refs(db, blob_path).await? > 0 {
+        return Ok(0);
+    }
+    match BLOB_STORE.remove(blob_path) {
+        Ok(size) => Ok(size),
+        Err(e) => {
+            error!("Failed to remove blob {}: {}", blob_path, e);
+            Ok(0)
+        }
+    }
 }
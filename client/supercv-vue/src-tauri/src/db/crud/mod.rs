@@ -1 +1,4 @@
+pub mod file_snapshot;
 pub mod host_clipboard;
+pub mod representation;
+pub mod tag;
@@ -0,0 +1,110 @@
+use crate::core::pasteboard::Representation as PasteboardRepresentation;
+use crate::db::entities::prelude::Representation;
+use crate::db::entities::representation;
+use sea_orm::ActiveValue::Set;
+use sea_orm::*;
+use std::fs;
+use std::io;
+
+// 保存一次复制的全部格式, 重复复制时更新已有的格式, 返回被替换掉的 blob 路径;
+// 大的格式已由 PasteboardContent::store_representations 写入 blob store
+pub async fn add_representations(
+    db: &DatabaseConnection,
+    clipboard_id: i32,
+    representations: Vec<PasteboardRepresentation>,
+) -> Result<Vec<String>, DbErr> {
+    let mut replaced = Vec::new();
+    for rep in representations {
+        let data = rep.path.is_none().then_some(rep.data);
+        let existing = Representation::find()
+            .filter(representation::Column::ClipboardId.eq(clipboard_id))
+            .filter(representation::Column::Format.eq(rep.format.as_str()))
+            .one(db)
+            .await?;
+        match existing {
+            Some(existing) if existing.path == rep.path && existing.data == data => {}
+            Some(existing) => {
+                let old_blob = existing.path.clone();
+                let mut existing: representation::ActiveModel = existing.into();
+                existing.data = Set(data);
+                existing.path = Set(rep.path.clone());
+                existing.size = Set(rep.size);
+                existing.update(db).await?;
+                replaced.extend(old_blob.filter(|old| Some(old) != rep.path.as_ref()));
+            }
+            None => {
+                representation::ActiveModel {
+                    clipboard_id: Set(clipboard_id),
+                    format: Set(rep.format),
+                    data: Set(data),
+                    path: Set(rep.path),
+                    size: Set(rep.size),
+                    ..Default::default()
+                }
+                .insert(db)
+                .await?;
+            }
+        }
+    }
+    Ok(replaced)
+}
+
+// 按复制时的顺序返回记录的全部格式
+pub async fn get_representations(
+    db: &DatabaseConnection,
+    clipboard_id: i32,
+) -> Result<Vec<representation::Model>, DbErr> {
+    Representation::find()
+        .filter(representation::Column::ClipboardId.eq(clipboard_id))
+        .order_by_asc(representation::Column::Id)
+        .all(db)
+        .await
+}
+
+// 这些记录引用的 blob 路径
+pub async fn get_blob_paths(
+    db: &DatabaseConnection,
+    clipboard_ids: Vec<i32>,
+) -> Result<Vec<String>, DbErr> {
+    let paths: Vec<Option<String>> = Representation::find()
+        .select_only()
+        .column(representation::Column::Path)
+        .distinct()
+        .filter(representation::Column::ClipboardId.is_in(clipboard_ids))
+        .filter(representation::Column::Path.is_not_null())
+        .into_tuple()
+        .all(db)
+        .await?;
+    Ok(paths.into_iter().flatten().collect())
+}
+
+pub async fn count_path_refs(db: &DatabaseConnection, path: &str) -> Result<u64, DbErr> {
+    Representation::find()
+        .filter(representation::Column::Path.eq(path))
+        .count(db)
+        .await
+}
+
+// blob 对应的格式, 相同内容的不同格式取最早的一个
+pub async fn get_format_by_path(
+    db: &DatabaseConnection,
+    path: &str,
+) -> Result<Option<String>, DbErr> {
+    Representation::find()
+        .select_only()
+        .column(representation::Column::Format)
+        .filter(representation::Column::Path.eq(path))
+        .order_by_asc(representation::Column::Id)
+        .into_tuple()
+        .one(db)
+        .await
+}
+
+// 读取格式内容, 大内容从 blob store 读取
+pub fn read_data(rep: &representation::Model) -> io::Result<Vec<u8>> {
+    match (&rep.data, &rep.path) {
+        (Some(data), _) => Ok(data.clone()),
+        (None, Some(path)) => fs::read(path),
+        (None, None) => Ok(Vec::new()),
+    }
+}
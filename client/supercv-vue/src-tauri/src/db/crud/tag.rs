@@ -0,0 +1,116 @@
+use crate::db::entities::prelude::{ClipboardTag, Tag};
+use crate::db::entities::{clipboard_tag, host_clipboard, tag};
+use crate::utils::time::get_current_timestamp;
+use sea_orm::sea_query::{Expr, OnConflict, Query, SelectStatement, SimpleExpr};
+use sea_orm::ActiveValue::Set;
+use sea_orm::*;
+
+pub async fn create_tag(db: &DatabaseConnection, name: &str) -> Result<tag::Model, DbErr> {
+    tag::ActiveModel {
+        name: Set(normalize_name(name)?),
+        timestamp: Set(get_current_timestamp()),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+}
+
+pub async fn rename_tag(db: &DatabaseConnection, id: i32, name: &str) -> Result<tag::Model, DbErr> {
+    let tag = Tag::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or(DbErr::RecordNotFound(format!("tag {} not found", id)))?;
+    let mut tag: tag::ActiveModel = tag.into();
+    tag.name = Set(normalize_name(name)?);
+    tag.update(db).await
+}
+
+// 删除标签, 关联关系由外键级联删除
+pub async fn delete_tag(db: &DatabaseConnection, id: i32) -> Result<DeleteResult, DbErr> {
+    Tag::delete_by_id(id).exec(db).await
+}
+
+pub async fn get_tags(db: &DatabaseConnection) -> Result<Vec<tag::Model>, DbErr> {
+    Tag::find().order_by_asc(tag::Column::Name).all(db).await
+}
+
+pub async fn get_tags_by_clipboard(
+    db: &DatabaseConnection,
+    clipboard_id: i32,
+) -> Result<Vec<tag::Model>, DbErr> {
+    Tag::find()
+        .inner_join(ClipboardTag)
+        .filter(clipboard_tag::Column::ClipboardId.eq(clipboard_id))
+        .order_by_asc(tag::Column::Name)
+        .all(db)
+        .await
+}
+
+pub async fn attach_tag(
+    db: &DatabaseConnection,
+    clipboard_id: i32,
+    tag_id: i32,
+) -> Result<(), DbErr> {
+    ClipboardTag::insert(clipboard_tag::ActiveModel {
+        clipboard_id: Set(clipboard_id),
+        tag_id: Set(tag_id),
+    })
+    .on_conflict(
+        OnConflict::columns([
+            clipboard_tag::Column::ClipboardId,
+            clipboard_tag::Column::TagId,
+        ])
+        .do_nothing()
+        .to_owned(),
+    )
+    .do_nothing()
+    .exec(db)
+    .await?;
+    Ok(())
+}
+
+pub async fn detach_tag(
+    db: &DatabaseConnection,
+    clipboard_id: i32,
+    tag_id: i32,
+) -> Result<DeleteResult, DbErr> {
+    ClipboardTag::delete_many()
+        .filter(clipboard_tag::Column::ClipboardId.eq(clipboard_id))
+        .filter(clipboard_tag::Column::TagId.eq(tag_id))
+        .exec(db)
+        .await
+}
+
+// 打了 tag_ids 中任一标签的记录 id
+pub(crate) fn clipboard_ids_by_tag_ids(tag_ids: Vec<i32>) -> SelectStatement {
+    Query::select()
+        .column(clipboard_tag::Column::ClipboardId)
+        .from(ClipboardTag)
+        .and_where(clipboard_tag::Column::TagId.is_in(tag_ids))
+        .to_owned()
+}
+
+// 打了名为 name 的标签的记录 id
+pub(crate) fn clipboard_ids_by_tag_name(name: &str) -> SelectStatement {
+    Query::select()
+        .column(clipboard_tag::Column::ClipboardId)
+        .from(ClipboardTag)
+        .inner_join(
+            Tag,
+            Expr::col((Tag, tag::Column::Id)).equals((ClipboardTag, clipboard_tag::Column::TagId)),
+        )
+        .and_where(Expr::col((Tag, tag::Column::Name)).eq(name))
+        .to_owned()
+}
+
+pub(crate) fn tagged_condition(subquery: SelectStatement) -> SimpleExpr {
+    host_clipboard::Column::Id.in_subquery(subquery)
+}
+
+fn normalize_name(name: &str) -> Result<String, DbErr> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(DbErr::Custom("Tag name can not be empty".to_string()));
+    }
+    Ok(name.to_string())
+}
@@ -13,9 +13,87 @@ pub struct Model {
     pub content: String,
     pub timestamp: i64,
     pub hash: String,
+    pub size: i64,
+    pub pinned: bool,
+    pub pin_order: Option<i32>,
+    pub source_app: Option<String>,
+    pub source_title: Option<String>,
+    pub html: Option<String>,
+    pub rtf: Option<String>,
+    pub subtype: Option<i32>,
+    pub broken: bool,
+    pub version_key: Option<String>,
+    pub summary: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
-pub enum Relation {}
+pub enum Relation {
+    #[sea_orm(has_many = "super::clipboard_tag::Entity")]
+    ClipboardTag,
+    #[sea_orm(has_many = "super::file_snapshot::Entity")]
+    FileSnapshot,
+    #[sea_orm(has_many = "super::representation::Entity")]
+    Representation,
+}
+
+impl Related<super::clipboard_tag::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ClipboardTag.def()
+    }
+}
+
+impl Related<super::file_snapshot::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::FileSnapshot.def()
+    }
+}
+
+impl Related<super::representation::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Representation.def()
+    }
+}
+
+impl Related<super::tag::Entity> for Entity {
+    fn to() -> RelationDef {
+        super::clipboard_tag::Relation::Tag.def()
+    }
+    fn via() -> Option<RelationDef> {
+        Some(super::clipboard_tag::Relation::HostClipboard.def().rev())
+    }
+}
 
 impl ActiveModelBehavior for ActiveModel {}
+
+// 多文件条目的 path 保存为 JSON 数组, 文件名中可能有换行等任何分隔符;
+// 单个路径直接保存, 绝对路径不会以 [ 开头
+pub fn encode_paths(paths: &[String]) -> String {
+    match paths {
+        [path] => path.clone(),
+        paths => serde_json::to_string(paths).unwrap(),
+    }
+}
+
+// 返回 path 中的全部路径, 单文件条目只有一个元素
+pub fn decode_paths(path: &str) -> Vec<String> {
+    if path.is_empty() {
+        return Vec::new();
+    }
+    if path.starts_with('[') {
+        if let Ok(paths) = serde_json::from_str(path) {
+            return paths;
+        }
+    }
+    vec![path.to_string()]
+}
+
+impl Model {
+    pub fn paths(&self) -> Vec<String> {
+        decode_paths(&self.path)
+    }
+
+    // 大文本的 path 指向 blob store 中压缩的全文, content 只是预览
+    pub fn is_large_text(&self) -> bool {
+        self.r#type == 0 && !self.path.is_empty()
+    }
+}
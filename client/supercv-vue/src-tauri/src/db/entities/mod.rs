@@ -2,4 +2,8 @@
 
 pub mod prelude;
 
+pub mod clipboard_tag;
+pub mod file_snapshot;
 pub mod host_clipboard;
+pub mod representation;
+pub mod tag;
@@ -1,2 +1,6 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.12.15
+pub use super::clipboard_tag::Entity as ClipboardTag;
+pub use super::file_snapshot::Entity as FileSnapshot;
 pub use super::host_clipboard::Entity as HostClipboard;
+pub use super::representation::Entity as Representation;
+pub use super::tag::Entity as Tag;
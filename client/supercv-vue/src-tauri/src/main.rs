@@ -4,18 +4,23 @@
 )]
 
 use std::sync::Arc;
+use std::time::Duration;
 use tauri::GlobalShortcutManager;
 use tauri::Manager;
 use tauri::{CustomMenuItem, SystemTray, SystemTrayEvent, SystemTrayMenu};
 
-use crate::clipboard_helper::{rs_invoke_get_clipboards, rs_invoke_get_user_config, rs_invoke_search_clipboards, rs_invoke_set_user_config, ClipboardHelper, rs_invoke_set_clipboards};
+use crate::clipboard_helper::{rs_invoke_get_clipboards, rs_invoke_get_user_config, rs_invoke_get_ignore_errors, rs_invoke_search_clipboards, rs_invoke_set_user_config, ClipboardHelper, rs_invoke_set_clipboards, rs_invoke_set_clipboards_transformed, rs_invoke_set_clipboards_joined, rs_invoke_get_representations, rs_invoke_purge_expired, rs_invoke_pin_clipboard, rs_invoke_unpin_clipboard, rs_invoke_get_pinned_clipboards, rs_invoke_reorder_pinned_clipboards, rs_invoke_get_clipboard_versions, rs_invoke_get_tags, rs_invoke_create_tag, rs_invoke_rename_tag, rs_invoke_delete_tag, rs_invoke_attach_tag, rs_invoke_detach_tag, rs_invoke_get_concealed_clipboards, rs_invoke_set_concealed_clipboard, rs_invoke_clear_concealed_clipboards, rs_invoke_get_capture_state, rs_invoke_pause_capture, rs_invoke_resume_capture, rs_invoke_get_paste_queue, rs_invoke_build_paste_queue, rs_invoke_advance_paste_queue, rs_invoke_clear_paste_queue};
+use crate::core::capture::CaptureState;
+use crate::core::paste_queue::QueueState;
 use crate::utils::config::CONFIG;
+use crate::utils::time::get_current_timestamp;
 use window_vibrancy::{apply_blur, apply_vibrancy, NSVisualEffectMaterial};
 
 
 mod clipboard_helper;
 mod core;
 mod db;
+mod protocol;
 mod utils;
 
 #[tauri::command]
@@ -29,6 +34,69 @@ fn rs_invoke_open_settings(window: tauri::Window) -> Result<(), String> {
     Ok(())
 }
 
+fn capture_tray_title(state: CaptureState) -> &'static str {
+    if state.is_running() {
+        "暂停记录"
+    } else {
+        "恢复记录"
+    }
+}
+
+// 根据记录状态更新托盘菜单并通知前端, 定时暂停到期后再刷新一次
+pub(crate) fn sync_capture_tray(app: &tauri::AppHandle, state: CaptureState) {
+    if let Err(e) = app
+        .tray_handle()
+        .get_item("pause_capture")
+        .set_title(capture_tray_title(state))
+    {
+        log::error!("Failed to update tray item: {}", e);
+    }
+    let _ = app.emit_all("capture_state_changed", state);
+
+    if let CaptureState::PausedUntil { until } = state {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            let secs = (until - get_current_timestamp()).max(0) as u64;
+            tokio::time::sleep(Duration::from_secs(secs)).await;
+            let current = app.state::<Arc<ClipboardHelper>>().capture_state();
+            if current.is_running() {
+                sync_capture_tray(&app, current);
+            }
+        });
+    }
+}
+
+// 粘贴队列不为空时注册, 粘贴当前条目后按下以换上下一条
+const PASTE_NEXT_SHORTCUT: &str = "CommandOrControl+Alt+V";
+
+// 通知前端队列变化, 只在队列不为空时占用全局快捷键
+pub(crate) fn sync_paste_queue(app: &tauri::AppHandle, state: QueueState) {
+    let _ = app.emit_all("paste_queue_changed", &state);
+
+    let mut shortcuts = app.global_shortcut_manager();
+    let registered = shortcuts.is_registered(PASTE_NEXT_SHORTCUT).unwrap_or(false);
+    let result = match (state.current.is_some(), registered) {
+        (true, false) => {
+            let app = app.clone();
+            shortcuts.register(PASTE_NEXT_SHORTCUT, move || {
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    let clipboard_helper = app.state::<Arc<ClipboardHelper>>();
+                    if let Err(e) = clipboard_helper.advance_paste_queue().await {
+                        log::error!("Failed to advance paste queue: {}", e);
+                    }
+                    sync_paste_queue(&app, clipboard_helper.paste_queue_state());
+                });
+            })
+        }
+        (false, true) => shortcuts.unregister(PASTE_NEXT_SHORTCUT),
+        _ => Ok(()),
+    };
+    if let Err(e) = result {
+        log::error!("Failed to update {} shortcut: {}", PASTE_NEXT_SHORTCUT, e);
+    }
+}
+
 #[tokio::main]
 async fn main() {
     let clipboard_helper = ClipboardHelper::new(None, Some(2)).await;
@@ -38,8 +106,13 @@ async fn main() {
     let quit = CustomMenuItem::new("quit".to_string(), "退出");
     let show_window = CustomMenuItem::new("show_window".to_string(), "显示页面");
     let setting = CustomMenuItem::new("setting".to_string(), "设置");
+    let pause_capture = CustomMenuItem::new(
+        "pause_capture".to_string(),
+        capture_tray_title(clipboard_helper.capture_state()),
+    );
     let tray_menu = SystemTrayMenu::new()
         .add_item(show_window)
+        .add_item(pause_capture)
         .add_item(setting)
         .add_item(quit);
     let system_tray = SystemTray::new().with_menu(tray_menu);
@@ -96,6 +169,27 @@ async fn main() {
                 }
             });
 
+            // 恢复上次的记录状态
+            let capture_state = app.state::<Arc<ClipboardHelper>>().capture_state();
+            sync_capture_tray(&app_handle, capture_state);
+
+            // 剪贴板被改写后队列已中断, 队首被粘贴后推进到下一条, 都通知前端
+            let queue_app = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                let clipboard_helper = queue_app.state::<Arc<ClipboardHelper>>();
+                loop {
+                    tokio::select! {
+                        _ = clipboard_helper.paste_queue_interrupted() => {}
+                        _ = clipboard_helper.paste_queue_pasted() => {
+                            if let Err(e) = clipboard_helper.advance_paste_queue().await {
+                                log::error!("Error advancing paste queue: {}", e);
+                            }
+                        }
+                    }
+                    sync_paste_queue(&queue_app, clipboard_helper.paste_queue_state());
+                }
+            });
+
             // 添加程序退出时的清理操作
             // let clipboard_helper = clipboard_helper_clone.clone();
             Ok(())
@@ -112,6 +206,18 @@ async fn main() {
                         window.set_focus().unwrap();
                     }
                 }
+                "pause_capture" => {
+                    let clipboard_helper = app.state::<Arc<ClipboardHelper>>();
+                    let new_state = if clipboard_helper.capture_state().is_running() {
+                        CaptureState::Paused
+                    } else {
+                        CaptureState::Running
+                    };
+                    match clipboard_helper.set_capture_state(new_state) {
+                        Ok(state) => sync_capture_tray(app, state),
+                        Err(e) => log::error!("Failed to set capture state: {}", e),
+                    }
+                }
                 "setting" => {
                     if let Some(settings) = app.get_window("settings") {
                         settings.show().unwrap();
@@ -123,12 +229,39 @@ async fn main() {
             _ => {}
         })
         .manage(clipboard_helper)
+        .register_uri_scheme_protocol(protocol::SCHEME, protocol::handle)
         .invoke_handler(tauri::generate_handler![
             rs_invoke_get_clipboards,
             rs_invoke_search_clipboards,
             rs_invoke_set_clipboards,
+            rs_invoke_set_clipboards_transformed,
+            rs_invoke_set_clipboards_joined,
+            rs_invoke_get_representations,
+            rs_invoke_pin_clipboard,
+            rs_invoke_unpin_clipboard,
+            rs_invoke_get_pinned_clipboards,
+            rs_invoke_reorder_pinned_clipboards,
+            rs_invoke_get_clipboard_versions,
+            rs_invoke_get_tags,
+            rs_invoke_create_tag,
+            rs_invoke_rename_tag,
+            rs_invoke_delete_tag,
+            rs_invoke_attach_tag,
+            rs_invoke_detach_tag,
+            rs_invoke_get_concealed_clipboards,
+            rs_invoke_set_concealed_clipboard,
+            rs_invoke_clear_concealed_clipboards,
+            rs_invoke_get_capture_state,
+            rs_invoke_pause_capture,
+            rs_invoke_resume_capture,
+            rs_invoke_get_paste_queue,
+            rs_invoke_build_paste_queue,
+            rs_invoke_advance_paste_queue,
+            rs_invoke_clear_paste_queue,
+            rs_invoke_purge_expired,
             rs_invoke_get_user_config,
             rs_invoke_set_user_config,
+            rs_invoke_get_ignore_errors,
             rs_invoke_open_settings
         ])
         .run(tauri::generate_context!())
@@ -0,0 +1,215 @@
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use log::{debug, error};
+use tauri::http::{header, Request, Response, ResponseBuilder, Uri};
+use tauri::{AppHandle, Manager};
+
+use crate::clipboard_helper::ClipboardHelper;
+use crate::utils::config::CONFIG;
+
+pub const SCHEME: &str = "supercv";
+
+// 内容以 hash 寻址, 不会变化
+const IMMUTABLE_CACHE: &str = "public, max-age=31536000, immutable";
+// 缩略图会随配置的尺寸重新生成, 每次通过 ETag 校验
+const REVALIDATE_CACHE: &str = "no-cache";
+
+#[derive(Debug, PartialEq)]
+pub enum Route {
+    Blob(String), // supercv://blob/<hash>
+    Thumb(i32),   // supercv://thumb/<id>
+}
+
+// 一个已解析到磁盘文件的资源
+pub struct Resource {
+    pub path: PathBuf,
+    pub mimetype: String,
+    pub etag: String,
+}
+
+// 同时支持 supercv://blob/<hash> 和 convertFileSrc 生成的 supercv://localhost/blob/<hash>,
+// Windows 上为 https://supercv.localhost/blob/<hash>
+pub fn parse_route(uri: &str) -> Option<Route> {
+    let uri = Uri::from_str(uri).ok()?;
+    let path = urlencoding::decode(uri.path()).ok()?;
+    let mut segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    match uri.host() {
+        Some(host) if host != "localhost" && !host.ends_with(".localhost") => {
+            segments.insert(0, host)
+        }
+        _ => {}
+    }
+    match segments.as_slice() {
+        ["blob", hash] if is_hash(hash) => Some(Route::Blob(hash.to_string())),
+        ["thumb", id] => id.parse().ok().map(Route::Thumb),
+        _ => None,
+    }
+}
+
+// hash 只能由字母和数字组成, 防止拼接出其他路径
+fn is_hash(hash: &str) -> bool {
+    !hash.is_empty() && hash.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+// 解析 Range: bytes=start-end, 返回 [start, end] 闭区间; 不支持多段范围
+pub fn parse_range(value: &str, len: u64) -> Option<(u64, u64)> {
+    let spec = value.trim().strip_prefix("bytes=")?;
+    if spec.contains(',') || len == 0 {
+        return None;
+    }
+    let (start, end) = spec.split_once('-')?;
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", suffix) => {
+            let suffix: u64 = suffix.parse().ok()?;
+            if suffix == 0 {
+                return None;
+            }
+            (len.saturating_sub(suffix), len - 1)
+        }
+        (start, "") => (start.parse().ok()?, len - 1),
+        (start, end) => (start.parse().ok()?, end.parse::<u64>().ok()?.min(len - 1)),
+    };
+    if start > end || start >= len {
+        return None;
+    }
+    Some((start, end))
+}
+
+// 只允许访问 files_path 下的文件
+fn check_scope(path: &Path) -> Result<PathBuf, Box<dyn Error>> {
+    let files_path = CONFIG.read().unwrap().files_path.clone();
+    let root = fs::canonicalize(files_path)?;
+    let path = fs::canonicalize(path)?;
+    if !path.starts_with(&root) {
+        return Err(format!("{} is outside the files directory", path.display()).into());
+    }
+    Ok(path)
+}
+
+pub fn handle(app: &AppHandle, request: &Request) -> Result<Response, Box<dyn Error>> {
+    let Some(route) = parse_route(request.uri()) else {
+        return status(404);
+    };
+
+    // 协议处理在主线程中同步执行
+    let clipboard_helper = app.state::<Arc<ClipboardHelper>>();
+    let resource = tokio::task::block_in_place(|| {
+        tokio::runtime::Handle::current().block_on(clipboard_helper.resolve_resource(&route))
+    });
+    let resource = match resource {
+        Ok(Some(resource)) => resource,
+        // 缩略图正在后台生成, 由前端稍后重试
+        Ok(None) => {
+            return ResponseBuilder::new()
+                .status(503)
+                .header(header::CACHE_CONTROL, "no-store")
+                .header(header::RETRY_AFTER, "1")
+                .body(Vec::new());
+        }
+        Err(e) => {
+            debug!("Failed to resolve {:?}: {}", route, e);
+            return status(404);
+        }
+    };
+    let path = match check_scope(&resource.path) {
+        Ok(path) => path,
+        Err(e) => {
+            error!("Refused to serve {:?}: {}", route, e);
+            return status(403);
+        }
+    };
+
+    let cache_control = match route {
+        Route::Blob(_) => IMMUTABLE_CACHE,
+        Route::Thumb(_) => REVALIDATE_CACHE,
+    };
+    let etag = format!("\"{}\"", resource.etag);
+    let builder = ResponseBuilder::new()
+        .mimetype(&resource.mimetype)
+        .header(header::CACHE_CONTROL, cache_control)
+        .header(header::ETAG, &etag)
+        .header(header::ACCEPT_RANGES, "bytes");
+
+    let if_none_match = request.headers().get(header::IF_NONE_MATCH);
+    if if_none_match.and_then(|v| v.to_str().ok()) == Some(etag.as_str()) {
+        return builder.status(304).body(Vec::new());
+    }
+
+    let mut file = File::open(&path)?;
+    let len = file.metadata()?.len();
+    let range = request
+        .headers()
+        .get(header::RANGE)
+        .and_then(|v| v.to_str().ok());
+    match range {
+        Some(range) => match parse_range(range, len) {
+            Some((start, end)) => {
+                let mut body = vec![0; (end - start + 1) as usize];
+                file.seek(SeekFrom::Start(start))?;
+                file.read_exact(&mut body)?;
+                builder
+                    .status(206)
+                    .header(
+                        header::CONTENT_RANGE,
+                        format!("bytes {}-{}/{}", start, end, len),
+                    )
+                    .body(body)
+            }
+            None => builder
+                .status(416)
+                .header(header::CONTENT_RANGE, format!("bytes */{}", len))
+                .body(Vec::new()),
+        },
+        None => {
+            let mut body = Vec::with_capacity(len as usize);
+            file.read_to_end(&mut body)?;
+            builder.body(body)
+        }
+    }
+}
+
+fn status(code: u16) -> Result<Response, Box<dyn Error>> {
+    ResponseBuilder::new().status(code).body(Vec::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_route() {
+        assert_eq!(
+            parse_route("supercv://blob/abc123"),
+            Some(Route::Blob("abc123".to_string()))
+        );
+        assert_eq!(
+            parse_route("supercv://localhost/blob/abc123"),
+            Some(Route::Blob("abc123".to_string()))
+        );
+        assert_eq!(
+            parse_route("https://supercv.localhost/thumb%2F42"),
+            Some(Route::Thumb(42))
+        );
+        assert_eq!(parse_route("supercv://thumb/42"), Some(Route::Thumb(42)));
+        assert_eq!(parse_route("supercv://blob/..%2F..%2Fetc"), None);
+        assert_eq!(parse_route("supercv://thumb/x"), None);
+        assert_eq!(parse_route("supercv://other/1"), None);
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("bytes=0-99", 1000), Some((0, 99)));
+        assert_eq!(parse_range("bytes=900-", 1000), Some((900, 999)));
+        assert_eq!(parse_range("bytes=-100", 1000), Some((900, 999)));
+        assert_eq!(parse_range("bytes=990-2000", 1000), Some((990, 999)));
+        assert_eq!(parse_range("bytes=1000-", 1000), None);
+        assert_eq!(parse_range("bytes=5-1", 1000), None);
+        assert_eq!(parse_range("bytes=0-1,5-6", 1000), None);
+        assert_eq!(parse_range("items=0-1", 1000), None);
+    }
+}
@@ -1,3 +1,5 @@
+use crate::core::concealed::CONCEALED_STASH;
+use crate::core::ignore::IgnoreRules;
 use crate::utils::time::get_current_timestamp;
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
@@ -15,9 +17,20 @@ pub async fn update(user_config: UserConfig) -> io::Result<()> {
     let (_, config_path) = get_paths(); // 获取配置路径
     let file_path = config_path.join("config.toml"); // 配置文件路径
 
+    // 忽略规则有误时拒绝保存
+    let (ignore_rules, errors) = IgnoreRules::new(&user_config.ignore_config);
+    if !errors.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, errors.join("; ")));
+    }
+
     {
         let mut config = CONFIG.write().unwrap(); // 获取写锁
         config.user_config = user_config;
+        config.ignore_rules = ignore_rules;
+        config.ignore_errors.clear();
+        if !config.user_config.concealed_config.keep {
+            CONCEALED_STASH.clear();
+        }
     } // 写锁在这里自动释放
 
     // 在调用异步操作之前复制所需的数据
@@ -40,19 +53,93 @@ pub struct ExpiredConfig {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PreviewConfig {
     pub preview_number: u32,
+    // 图片缩略图最长边的像素数
+    #[serde(default = "default_thumbnail_size")]
+    pub thumbnail_size: u32,
+}
+
+fn default_thumbnail_size() -> u32 {
+    256
+}
+
+// 不记录的剪贴板内容
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct IgnoreConfig {
+    pub apps: Vec<String>,     // 来源应用的 WM_CLASS, 不区分大小写
+    pub patterns: Vec<String>, // 匹配文本内容的正则
+    pub paths: Vec<String>,    // 匹配文件路径的 glob, 支持 ~
+}
+
+// 密码管理器标记为隐藏的内容, 不会写入数据库
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ConcealedConfig {
+    pub keep: bool,   // 是否在内存中临时保留
+    pub timeout: u64, // 保留的秒数
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TextConfig {
+    pub max_size: u64, // 文本大小上限, 单位 MB, 超过时不记录
+}
+
+impl TextConfig {
+    pub fn max_size_bytes(&self) -> usize {
+        (self.max_size as usize).saturating_mul(1024 * 1024)
+    }
+}
+
+// 复制文件时保存副本, 原文件被移动或删除后仍可粘贴
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SnapshotConfig {
+    pub enabled: bool,
+    pub max_size: u64, // 单个文件大小上限, 单位 MB
+}
+
+impl SnapshotConfig {
+    pub fn max_size_bytes(&self) -> u64 {
+        self.max_size.saturating_mul(1024 * 1024)
+    }
+}
+
+// 粘贴图片条目时同时提供文件路径, 便于粘贴到文件管理器
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ImageConfig {
+    pub paste_file_uri: bool,
+}
+
+// 粘贴条目后是否把它移到最前
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PasteConfig {
+    pub move_to_top: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct UserConfig {
     pub expired_config: ExpiredConfig,
     pub preview_config: PreviewConfig,
+    // 旧配置文件中没有该项
+    #[serde(default)]
+    pub ignore_config: IgnoreConfig,
+    #[serde(default)]
+    pub concealed_config: ConcealedConfig,
+    #[serde(default)]
+    pub text_config: TextConfig,
+    #[serde(default)]
+    pub snapshot_config: SnapshotConfig,
+    #[serde(default)]
+    pub image_config: ImageConfig,
+    #[serde(default)]
+    pub paste_config: PasteConfig,
 }
 
 pub struct Config {
     pub db_path: PathBuf,
     pub files_path: PathBuf,
     pub logs_path: PathBuf,
+    pub config_dir: PathBuf,
     pub user_config: UserConfig,
+    pub ignore_rules: IgnoreRules,
+    pub ignore_errors: Vec<String>, // 启动时无法解析的忽略规则, 其余规则照常生效
 }
 
 impl Config {
@@ -69,12 +156,17 @@ impl Config {
         }
 
         let user_config = UserConfig::load(&config_dir).expect("加载用户配置失败");
+        // 此时日志还未初始化, 错误由 ClipboardHelper 记录并在设置页面展示
+        let (ignore_rules, ignore_errors) = IgnoreRules::new(&user_config.ignore_config);
 
         Self {
             db_path,
             files_path,
             logs_path,
+            config_dir,
             user_config,
+            ignore_rules,
+            ignore_errors,
         }
     }
 
@@ -151,7 +243,46 @@ impl Default for UserConfig {
                 img: 3,
                 file: 3,
             },
-            preview_config: PreviewConfig { preview_number: 20 },
+            preview_config: PreviewConfig {
+                preview_number: 20,
+                thumbnail_size: default_thumbnail_size(),
+            },
+            ignore_config: IgnoreConfig::default(),
+            concealed_config: ConcealedConfig::default(),
+            text_config: TextConfig::default(),
+            snapshot_config: SnapshotConfig::default(),
+            image_config: ImageConfig::default(),
+            paste_config: PasteConfig::default(),
+        }
+    }
+}
+
+impl Default for ConcealedConfig {
+    fn default() -> Self {
+        Self {
+            keep: false,
+            timeout: 30,
+        }
+    }
+}
+
+impl Default for TextConfig {
+    fn default() -> Self {
+        Self { max_size: 16 }
+    }
+}
+
+impl Default for PasteConfig {
+    fn default() -> Self {
+        Self { move_to_top: true }
+    }
+}
+
+impl Default for SnapshotConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_size: 20,
         }
     }
 }
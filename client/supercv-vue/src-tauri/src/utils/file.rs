@@ -18,12 +18,23 @@ pub fn format_size(size: usize) -> String {
 }
 
 pub fn get_file_size(file_path: &String) -> String {
-    let size = match fs::metadata(file_path) {
+    format_size(get_file_size_bytes(file_path) as usize)
+}
+
+pub fn get_file_size_bytes(file_path: &str) -> u64 {
+    match fs::metadata(file_path) {
         Ok(metadata) => metadata.len(),
         Err(e) => {
             error!("Failed to get file size: {}, ", e);
             0
         }
-    };
-    format_size(size as usize)
+    }
+}
+
+// 将开头的 ~ 展开为用户家目录
+pub fn expand_home(path: &str) -> String {
+    match (path.strip_prefix('~'), dirs::home_dir()) {
+        (Some(rest), Some(home)) => format!("{}{}", home.display(), rest),
+        _ => path.to_string(),
+    }
 }
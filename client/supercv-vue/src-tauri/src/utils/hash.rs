@@ -1,4 +1,8 @@
-use twox_hash::xxh3::hash64;
+use std::fs::File;
+use std::hash::Hasher;
+use std::io::{self, Read};
+use std::path::Path;
+use twox_hash::xxh3::{hash64, Hash64};
 
 pub fn hash_str(input: &str) -> String {
     format!("{:x}", hash64(input.as_bytes()))
@@ -7,3 +11,18 @@ pub fn hash_str(input: &str) -> String {
 pub fn hash_vec(input: &[u8]) -> String {
     format!("{:x}", hash64(input))
 }
+
+// 分块读取文件计算 hash, 不把整个文件读入内存
+pub fn hash_file<P: AsRef<Path>>(path: P) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Hash64::with_seed(0);
+    let mut buf = vec![0; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.write(&buf[..n]);
+    }
+    Ok(format!("{:x}", hasher.finish()))
+}
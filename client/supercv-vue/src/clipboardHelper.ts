@@ -1,4 +1,4 @@
-import { invoke } from "@tauri-apps/api/tauri";
+import { invoke, convertFileSrc } from "@tauri-apps/api/tauri";
 
 export interface ClipboardEntry {
     id: number;
@@ -7,6 +7,87 @@ export interface ClipboardEntry {
     content: string;
     timestamp: number;
     hash: string;
+    size: number;
+    pinned: boolean;
+    pin_order: number | null;
+    // 来源应用(WM_CLASS)和窗口标题, 未知时为 null
+    source_app: string | null;
+    source_title: string | null;
+    // 富文本(type 3)的 HTML 和 RTF
+    html: string | null;
+    rtf: string | null;
+    // 文本的细分类型, 见 TextSubtype
+    subtype: number | null;
+    // 原文件已不存在且没有副本, 无法粘贴
+    broken: boolean;
+    // 同一路径文件的不同版本共用, 非文件条目为 null
+    version_key: string | null;
+    // 目录(type 4)的统计信息, DirSummary 的 JSON
+    summary: string | null;
+}
+
+// 目录的递归统计和第一层列表
+export interface DirSummary {
+    file_count: number;
+    dir_count: number;
+    total_size: number;
+    entries: { name: string; is_dir: boolean; size: number }[];
+    truncated: boolean;
+}
+
+// 与 rust 端 TextSubtype::to_i32 一致
+export enum TextSubtype {
+    Url = 0,
+    Email = 1,
+    FilePath = 2,
+    Color = 3,
+    Json = 4,
+    Uuid = 5,
+    Ip = 6,
+    Phone = 7,
+    Timestamp = 8,
+    Code = 9,
+}
+
+// 一次复制提供的某种格式, 内容不返回给前端
+export interface Representation {
+    id: number;
+    clipboard_id: number;
+    format: string;
+    path: string | null;
+    size: number;
+}
+
+export interface Tag {
+    id: number;
+    name: string;
+    timestamp: number;
+}
+
+export interface SearchHit extends ClipboardEntry {
+    // 命中位置 [start, end)
+    highlights: [number, number][];
+}
+
+// rs_invoke_search_clipboards 的错误
+export type SearchError =
+    | { kind: "parse"; message: string; token: string; offset: number }
+    | { kind: "database"; message: string };
+
+// 剪贴板记录状态, paused_until 到期后自动恢复
+export type CaptureState =
+    | { state: "running" }
+    | { state: "paused" }
+    | { state: "paused_until"; until: number };
+
+// 依次粘贴的队列, 与 rust 端 QueueState 一致
+export type QueueMode = "fifo" | "lifo";
+
+export interface QueueState {
+    mode: QueueMode;
+    current: number | null; // 当前在剪贴板中的条目 id
+    pending: number[];
+    total: number;
 }
 
 export interface ExpiredConfig {
@@ -16,17 +97,95 @@ export interface ExpiredConfig {
 }
 export interface PreviewConfig {
     preview_number: number;
+    thumbnail_size: number; // 缩略图最长边的像素数
+}
+
+// 不记录的剪贴板内容
+export interface IgnoreConfig {
+    apps: string[]; // 来源应用的 WM_CLASS
+    patterns: string[]; // 匹配文本的正则
+    paths: string[]; // 匹配文件路径的 glob
+}
+
+// 密码管理器标记为隐藏的内容
+export interface ConcealedConfig {
+    keep: boolean; // 是否在内存中临时保留
+    timeout: number; // 保留的秒数
+}
+
+export interface TextConfig {
+    max_size: number; // 超过该大小(MB)的文本不记录
+}
+
+export interface SnapshotConfig {
+    enabled: boolean; // 复制文件时保存副本
+    max_size: number; // 单个文件大小上限(MB)
+}
+
+export interface ImageConfig {
+    paste_file_uri: boolean; // 粘贴图片时同时提供文件路径
+}
+
+// 与 rust 端 Transform 一致, 粘贴前按顺序执行
+export type Transform =
+    | "trim"
+    | "lowercase"
+    | "uppercase"
+    | "title_case"
+    | "join_lines"
+    | "json_pretty"
+    | "json_minify"
+    | "url_encode"
+    | "url_decode"
+    | "base64_encode"
+    | "base64_decode"
+    | "shell_escape"
+    | "json_escape"
+    | "strip_formatting";
+
+// 多条文本粘贴时的连接方式, 与 rust 端 JoinOptions 一致
+export type Separator =
+    | { kind: "newline" }
+    | { kind: "comma" }
+    | { kind: "tab" }
+    | { kind: "custom"; value: string };
+
+export interface JoinOptions {
+    separator: Separator;
+    numbering: boolean;
+    quote: "double" | "single" | null;
+}
+
+export interface PasteConfig {
+    move_to_top: boolean; // 粘贴后把条目移到最前
 }
 
 export interface UserConfig {
     expired_config: ExpiredConfig;
     preview_config: PreviewConfig;
+    ignore_config: IgnoreConfig;
+    concealed_config: ConcealedConfig;
+    text_config: TextConfig;
+    snapshot_config: SnapshotConfig;
+    image_config: ImageConfig;
+    paste_config: PasteConfig;
+}
+
+// 只保存在内存中的隐藏内容, 不包含内容本身
+export interface ConcealedClip {
+    id: number;
+    source_app: string | null;
+    timestamp: number;
+    expires_at: number;
 }
 
 export class ClipboardHelper {
     static async getClipboardEntries(
         num: number = 10,
-        typeList: number[] | null = null
+        typeList: number[] | null = null,
+        tagList: number[] | null = null,
+        subtypeList: number[] | null = null,
+        groupVersions: boolean = false
     ): Promise<ClipboardEntry[]> {
         try {
             const result = await invoke<ClipboardEntry[]>(
@@ -34,6 +193,9 @@ export class ClipboardHelper {
                 {
                     num,
                     typeList,
+                    tagList,
+                    subtypeList,
+                    groupVersions,
                 }
             );
             console.log(result);
@@ -48,25 +210,157 @@ export class ClipboardHelper {
     static async searchClipboardEntries(
         query: string,
         num: number = 10,
-        typeList: number[] | null = null
-    ): Promise<ClipboardEntry[]> {
+        typeList: number[] | null = null,
+        tagList: number[] | null = null
+    ): Promise<SearchHit[]> {
         try {
-            const result = await invoke<ClipboardEntry[]>(
+            const result = await invoke<SearchHit[]>(
                 "rs_invoke_search_clipboards",
                 {
                     query,
                     num,
                     typeList,
+                    tagList,
                 }
             );
             return result;
         } catch (error) {
             console.error("Failed to search clipboard entries:", error);
-            // throw error;
+            // 查询语法错误交给调用方提示用户
+            if ((error as SearchError)?.kind === "parse") {
+                throw error;
+            }
+            return [];
+        }
+    }
+
+    static async getRepresentations(clipboardId: number): Promise<Representation[]> {
+        try {
+            return await invoke<Representation[]>("rs_invoke_get_representations", { clipboardId });
+        } catch (error) {
+            console.error("Failed to get representations:", error);
+            return [];
+        }
+    }
+
+    // 图片记录的缩略图地址, 由 supercv 自定义协议提供
+    static thumbnailSrc(id: number): string {
+        return convertFileSrc(`thumb/${id}`, "supercv");
+    }
+
+    // blob store 中以 hash 保存的原图或格式内容
+    static blobSrc(hash: string): string {
+        return convertFileSrc(`blob/${hash}`, "supercv");
+    }
+
+    static async pinClipboardEntry(id: number): Promise<ClipboardEntry> {
+        return await invoke<ClipboardEntry>("rs_invoke_pin_clipboard", { id });
+    }
+
+    static async unpinClipboardEntry(id: number): Promise<ClipboardEntry> {
+        return await invoke<ClipboardEntry>("rs_invoke_unpin_clipboard", { id });
+    }
+
+    static async getPinnedClipboardEntries(): Promise<ClipboardEntry[]> {
+        try {
+            return await invoke<ClipboardEntry[]>("rs_invoke_get_pinned_clipboards");
+        } catch (error) {
+            console.error("Failed to get pinned clipboard entries:", error);
             return [];
         }
     }
 
+    // 与 id 对应的记录属于同一文件的全部版本, 最新的在前
+    static async getClipboardVersions(id: number): Promise<ClipboardEntry[]> {
+        try {
+            return await invoke<ClipboardEntry[]>("rs_invoke_get_clipboard_versions", { id });
+        } catch (error) {
+            console.error("Failed to get clipboard versions:", error);
+            return [];
+        }
+    }
+
+    static async reorderPinnedClipboardEntries(idList: number[]): Promise<void> {
+        await invoke<boolean>("rs_invoke_reorder_pinned_clipboards", { idList });
+    }
+
+    // clipboardId 为空时返回全部标签
+    static async getTags(clipboardId: number | null = null): Promise<Tag[]> {
+        try {
+            return await invoke<Tag[]>("rs_invoke_get_tags", { clipboardId });
+        } catch (error) {
+            console.error("Failed to get tags:", error);
+            return [];
+        }
+    }
+
+    static async createTag(name: string): Promise<Tag> {
+        return await invoke<Tag>("rs_invoke_create_tag", { name });
+    }
+
+    static async renameTag(id: number, name: string): Promise<Tag> {
+        return await invoke<Tag>("rs_invoke_rename_tag", { id, name });
+    }
+
+    static async deleteTag(id: number): Promise<void> {
+        await invoke<boolean>("rs_invoke_delete_tag", { id });
+    }
+
+    static async attachTag(clipboardId: number, tagId: number): Promise<void> {
+        await invoke<boolean>("rs_invoke_attach_tag", { clipboardId, tagId });
+    }
+
+    static async detachTag(clipboardId: number, tagId: number): Promise<void> {
+        await invoke<boolean>("rs_invoke_detach_tag", { clipboardId, tagId });
+    }
+
+    static async getConcealedClipboardEntries(): Promise<ConcealedClip[]> {
+        try {
+            return await invoke<ConcealedClip[]>("rs_invoke_get_concealed_clipboards");
+        } catch (error) {
+            console.error("Failed to get concealed clipboard entries:", error);
+            return [];
+        }
+    }
+
+    static async setConcealedClipboardEntry(id: number): Promise<void> {
+        await invoke<boolean>("rs_invoke_set_concealed_clipboard", { id });
+    }
+
+    static async clearConcealedClipboardEntries(): Promise<void> {
+        await invoke<boolean>("rs_invoke_clear_concealed_clipboards");
+    }
+
+    static async getCaptureState(): Promise<CaptureState> {
+        return await invoke<CaptureState>("rs_invoke_get_capture_state");
+    }
+
+    // minutes 为空时一直暂停, 直到手动恢复
+    static async pauseCapture(minutes: number | null = null): Promise<CaptureState> {
+        return await invoke<CaptureState>("rs_invoke_pause_capture", { minutes });
+    }
+
+    static async resumeCapture(): Promise<CaptureState> {
+        return await invoke<CaptureState>("rs_invoke_resume_capture");
+    }
+
+    static async getPasteQueue(): Promise<QueueState> {
+        return await invoke<QueueState>("rs_invoke_get_paste_queue");
+    }
+
+    // 队首条目立即放到剪贴板, 之后由快捷键或 advancePasteQueue 换下一条
+    static async buildPasteQueue(idList: number[], mode: QueueMode = "fifo"): Promise<QueueState> {
+        return await invoke<QueueState>("rs_invoke_build_paste_queue", { idList, mode });
+    }
+
+    static async advancePasteQueue(): Promise<QueueState> {
+        return await invoke<QueueState>("rs_invoke_advance_paste_queue");
+    }
+
+    static async clearPasteQueue(): Promise<QueueState> {
+        return await invoke<QueueState>("rs_invoke_clear_paste_queue");
+    }
+
     static async setClipboardEntriy(
         item: ClipboardEntry
     ): Promise<void> {
@@ -79,6 +373,28 @@ export class ClipboardHelper {
             throw error;
         }
     }
+
+    // 按 items 的顺序连接文本; 文件和图片以文件列表粘贴
+    static async setClipboardEntriesJoined(
+        items: ClipboardEntry[],
+        options: JoinOptions
+    ): Promise<void> {
+        await invoke<boolean>("rs_invoke_set_clipboards_joined", {
+            items,
+            options,
+        });
+    }
+
+    // 只适用于文本条目, 转换结果以纯文本粘贴
+    static async setClipboardEntryTransformed(
+        item: ClipboardEntry,
+        transforms: Transform[]
+    ): Promise<void> {
+        await invoke<boolean>("rs_invoke_set_clipboards_transformed", {
+            item,
+            transforms,
+        });
+    }
 }
 
 export class UserConfig {
@@ -102,4 +418,14 @@ export class UserConfig {
             throw error;
         }
     }
+
+    // 启动时无法解析的忽略规则
+    static async getIgnoreErrors(): Promise<string[]> {
+        try {
+            return await invoke<string[]>("rs_invoke_get_ignore_errors");
+        } catch (error) {
+            console.error("Failed to get ignore errors:", error);
+            throw error;
+        }
+    }
 }